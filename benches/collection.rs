@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use robotics_lib::world::tile::{Content, Tile, TileType};
+
+#[cfg(feature = "rayon")]
+use another_one_bytes_the_dust_tile_resource_mapper_tool::tool::tile_mapper::scan_parallel;
+use another_one_bytes_the_dust_tile_resource_mapper_tool::tool::tile_mapper::scan_sequential;
+
+/// Builds a fully discovered `size`x`size` world with some content on one tile out of five.
+fn world(size: usize) -> Vec<Vec<Option<Tile>>> {
+    let mut seed: usize = 42;
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| {
+                    seed = (seed * 1103515245 + 12345) % 2147483648;
+                    let content = match seed % 20 {
+                        0 => Content::Rock(seed % 5),
+                        1 => Content::Tree(seed % 5),
+                        2 => Content::Coin(seed % 5),
+                        3 => Content::Garbage(seed % 5),
+                        _ => Content::None,
+                    };
+                    Some(Tile {
+                        tile_type: TileType::Grass,
+                        content,
+                        elevation: 0,
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn collection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collection");
+    group.sample_size(20);
+    for size in [250, 1000] {
+        let map = world(size);
+        group.bench_with_input(BenchmarkId::new("sequential", size), &map, |b, map| {
+            b.iter(|| scan_sequential(black_box(map)))
+        });
+        #[cfg(feature = "rayon")]
+        group.bench_with_input(BenchmarkId::new("parallel", size), &map, |b, map| {
+            b.iter(|| scan_parallel(black_box(map)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, collection);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::mem::discriminant;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use robotics_lib::world::tile::Content;
+
+use another_one_bytes_the_dust_tile_resource_mapper_tool::coordinates::map_coordinate::MapCoordinate;
+use another_one_bytes_the_dust_tile_resource_mapper_tool::spatial::spatial_index::SpatialIndex;
+use another_one_bytes_the_dust_tile_resource_mapper_tool::tool::tile_mapper::{
+    ResourceMap, TileMapper,
+};
+
+/// Builds a resource map of a `size`x`size` world where roughly one tile out of `sparsity` holds a coin.
+fn resources(size: usize, sparsity: usize) -> ResourceMap {
+    let mut seed: usize = 42;
+    let mut tiles = vec![];
+    for height in 0..size {
+        for width in 0..size {
+            seed = (seed * 1103515245 + 12345) % 2147483648;
+            if seed % sparsity == 0 {
+                tiles.push((MapCoordinate::new(width, height), (Some(seed % 10), None)));
+            }
+        }
+    }
+    let mut resources = HashMap::new();
+    resources.insert(discriminant(&Content::Coin(0)), tiles);
+    resources
+}
+
+fn nearest(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nearest coin");
+    for size in [250, 1000] {
+        let resources = resources(size, 50);
+        let spatial = SpatialIndex::from_resources(&resources);
+        let mapper = TileMapper {};
+        let origin = MapCoordinate::new(size / 3, size / 2);
+
+        group.bench_with_input(BenchmarkId::new("linear scan", size), &size, |b, _| {
+            b.iter(|| mapper.find_closest(black_box(&resources), &origin, Content::Coin(0)))
+        });
+        group.bench_with_input(BenchmarkId::new("spatial index", size), &size, |b, _| {
+            b.iter(|| spatial.nearest(black_box(&Content::Coin(0)), &origin))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("spatial index, 10 nearest", size),
+            &size,
+            |b, _| b.iter(|| spatial.k_nearest(black_box(&Content::Coin(0)), &origin, 10)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, nearest);
+criterion_main!(benches);
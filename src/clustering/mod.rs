@@ -0,0 +1,291 @@
+pub mod deposits {
+    use std::collections::{HashMap, VecDeque};
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::world::tile::Content;
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{ContentNotDiscovered, WorldNotDiscovered};
+    use crate::source::map_source::MapSource;
+    use crate::spatial::spatial_index::SpatialIndex;
+    use crate::tool::tile_mapper::{stored_amount, ContentQuantity, ResourceMap, TileMapper};
+
+    /// How tiles holding the same kind of `Content` are grouped into deposits.
+    ///
+    /// ## Variants
+    ///
+    /// - `Connected`: tiles sharing a side belong to the same deposit; with `diagonal` set,
+    ///   tiles sharing only a corner do too.
+    /// - `Radius`: DBSCAN-style clustering. A tile with at least `min_tiles` tiles (itself included)
+    ///   within `radius` is a core tile, and every tile within `radius` of a core tile belongs to its
+    ///   deposit. Tiles that are not close to any core tile form a deposit on their own.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum ClusterMode {
+        Connected { diagonal: bool },
+        Radius { radius: f64, min_tiles: usize },
+    }
+
+    impl Default for ClusterMode {
+        fn default() -> Self {
+            ClusterMode::Connected { diagonal: false }
+        }
+    }
+
+    /// A group of nearby tiles holding the same kind of `Content`.
+    ///
+    /// ## Fields
+    ///
+    /// - `kind`: The kind of `Content` of the deposit.
+    /// - `tiles`: The tiles of the deposit, in reading order, with the quantity they hold.
+    /// - `total_quantity`: The sum of the amounts held by the tiles.
+    /// - `centroid`: The mean `(width, height)` of the tiles.
+    /// - `entry_point`: The tile of the deposit closest to the position the deposits were computed from.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Deposit {
+        pub kind: Discriminant<Content>,
+        pub tiles: Vec<(MapCoordinate, ContentQuantity)>,
+        pub total_quantity: usize,
+        pub centroid: (f64, f64),
+        pub entry_point: MapCoordinate,
+    }
+
+    impl Deposit {
+        fn new(
+            kind: Discriminant<Content>,
+            mut tiles: Vec<(MapCoordinate, ContentQuantity)>,
+            origin: &MapCoordinate,
+        ) -> Self {
+            tiles.sort_by(|a, b| a.0.cmp(&b.0));
+            let count = tiles.len() as f64;
+            let total_quantity = tiles.iter().map(|(_, q)| stored_amount(q)).sum();
+            let centroid = (
+                tiles.iter().map(|(c, _)| c.get_width() as f64).sum::<f64>() / count,
+                tiles
+                    .iter()
+                    .map(|(c, _)| c.get_height() as f64)
+                    .sum::<f64>()
+                    / count,
+            );
+            // the first closest tile in reading order, so ties are broken deterministically
+            let mut entry_point = tiles[0].0;
+            for (coordinate, _) in tiles.iter() {
+                if coordinate.get_distance(origin) < entry_point.get_distance(origin) {
+                    entry_point = *coordinate;
+                }
+            }
+            Deposit {
+                kind,
+                tiles,
+                total_quantity,
+                centroid,
+                entry_point,
+            }
+        }
+
+        /// Gets the number of tiles of the deposit.
+        pub fn size(&self) -> usize {
+            self.tiles.len()
+        }
+    }
+
+    /// Returns the indexes of the tiles adjacent to `coordinate`.
+    fn adjacent(
+        coordinate: &MapCoordinate,
+        positions: &HashMap<MapCoordinate, usize>,
+        diagonal: bool,
+    ) -> Vec<usize> {
+        let (w, h) = (
+            coordinate.get_width() as i64,
+            coordinate.get_height() as i64,
+        );
+        let mut result = vec![];
+        for dh in -1..=1_i64 {
+            for dw in -1..=1_i64 {
+                let is_diagonal = dw != 0 && dh != 0;
+                if (dw == 0 && dh == 0) || (is_diagonal && !diagonal) || w + dw < 0 || h + dh < 0 {
+                    continue;
+                }
+                let neighbour = MapCoordinate::new((w + dw) as usize, (h + dh) as usize);
+                if let Some(index) = positions.get(&neighbour) {
+                    result.push(*index);
+                }
+            }
+        }
+        result
+    }
+
+    /// Groups the indexes of `tiles`, all holding the same kind of `content`, according to `mode`.
+    fn cluster(
+        content: &Content,
+        tiles: &[(MapCoordinate, ContentQuantity)],
+        mode: ClusterMode,
+    ) -> Vec<Vec<usize>> {
+        let positions: HashMap<MapCoordinate, usize> = tiles
+            .iter()
+            .enumerate()
+            .map(|(i, (c, _))| (*c, i))
+            .collect();
+        let neighbours: Vec<Vec<usize>> = match mode {
+            ClusterMode::Connected { diagonal } => tiles
+                .iter()
+                .map(|(c, _)| adjacent(c, &positions, diagonal))
+                .collect(),
+            ClusterMode::Radius { radius, .. } => {
+                // buckets as large as the radius, so that a neighbourhood spans a few of them
+                let mut spatial = SpatialIndex::with_cell_size(radius.ceil() as usize);
+                for (coordinate, quantity) in tiles.iter() {
+                    spatial.insert(content, *coordinate, quantity.clone());
+                }
+                tiles
+                    .iter()
+                    .map(|(c, _)| {
+                        let mut near: Vec<usize> = spatial
+                            .in_range(content, c, radius)
+                            .into_iter()
+                            .filter(|(coordinate, _)| coordinate != c)
+                            .map(|(coordinate, _)| positions[coordinate])
+                            .collect();
+                        // in the order of `tiles`, as the deposits a border tile joins depend on it
+                        near.sort_unstable();
+                        near
+                    })
+                    .collect()
+            }
+        };
+        // only core tiles can extend a deposit
+        let is_core: Vec<bool> = match mode {
+            ClusterMode::Connected { .. } => vec![true; tiles.len()],
+            ClusterMode::Radius { min_tiles, .. } => neighbours
+                .iter()
+                .map(|n| n.len() + 1 >= min_tiles)
+                .collect(),
+        };
+
+        // visit core tiles first, so that border tiles join a deposit instead of starting their own
+        let mut starts: Vec<usize> = (0..tiles.len()).collect();
+        starts.sort_by_key(|i| !is_core[*i]);
+
+        let mut assigned = vec![false; tiles.len()];
+        let mut clusters = vec![];
+        for start in starts {
+            if assigned[start] {
+                continue;
+            }
+            // breadth-first visit starting from an unassigned tile
+            assigned[start] = true;
+            let mut members = vec![start];
+            let mut queue = VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                if !is_core[current] {
+                    continue;
+                }
+                for next in neighbours[current].iter() {
+                    if !assigned[*next] {
+                        assigned[*next] = true;
+                        members.push(*next);
+                        queue.push_back(*next);
+                    }
+                }
+            }
+            clusters.push(members);
+        }
+        clusters
+    }
+
+    impl TileMapper {
+        /// Groups the tiles holding the same kind of `content` into deposits.
+        ///
+        /// # Arguments
+        ///
+        /// * `resources` - A resource map, as returned by `collection`.
+        /// * `content` - The Content we are looking for.
+        /// * `mode` - How the tiles are grouped.
+        /// * `origin` - The position used to choose the entry point of every deposit.
+        ///
+        /// # Returns
+        ///
+        /// Returns the deposits sorted from the richest to the poorest; deposits with the same
+        /// total quantity are sorted by the distance of their entry point from `origin`.
+        /// Returns an empty vector if no tile with `content` is in the map.
+        pub fn find_deposits(
+            resources: &ResourceMap,
+            content: &Content,
+            mode: ClusterMode,
+            origin: MapCoordinate,
+        ) -> Vec<Deposit> {
+            let kind = discriminant(content);
+            let tiles = match resources.get(&kind) {
+                Some(tiles) => tiles,
+                None => return vec![],
+            };
+
+            let mut deposits: Vec<Deposit> = cluster(content, tiles, mode)
+                .into_iter()
+                .map(|members| {
+                    let members = members.into_iter().map(|i| tiles[i].clone()).collect();
+                    Deposit::new(kind, members, &origin)
+                })
+                .collect();
+            deposits.sort_by(|a, b| {
+                b.total_quantity.cmp(&a.total_quantity).then(
+                    a.entry_point
+                        .get_distance(&origin)
+                        .total_cmp(&b.entry_point.get_distance(&origin)),
+                )
+            });
+            deposits
+        }
+
+        /// Finds the deposit holding the most Content, given a type of Content.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `origin` - The position distances are measured from, e.g. the robot or a `MapCoordinate`.
+        /// * `content` - The Content we're searching for.
+        /// * `mode` - How the tiles are grouped into deposits.
+        ///
+        /// # Returns
+        ///
+        /// Returns the richest deposit, whose `entry_point` is the tile of the deposit closest to the robot.
+        /// If no tile is found an error is returned.
+        ///
+        /// # Notes
+        ///
+        /// In case of two deposits with the same amount of Content, the closest one to the robot is returned.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::clustering::deposits::ClusterMode;
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// let result = mapper.find_richest_deposit(world, robot, Content::Tree(0), ClusterMode::default());
+        ///
+        /// match result {
+        ///     Ok(deposit) => println!("{} trees, enter at {}", deposit.total_quantity, deposit.entry_point),
+        ///     Err(e) => println!("{}", e)
+        /// }
+        /// ```
+        pub fn find_richest_deposit<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            content: Content,
+            mode: ClusterMode,
+        ) -> Result<Deposit, ToolError> {
+            match source.resources() {
+                Some(map) => {
+                    let origin_coordinates = origin.location();
+                    TileMapper::find_deposits(&map, &content, mode, origin_coordinates)
+                        .into_iter()
+                        .next()
+                        .ok_or(ContentNotDiscovered(discriminant(&content)))
+                }
+                None => Err(WorldNotDiscovered),
+            }
+        }
+    }
+}
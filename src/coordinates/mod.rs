@@ -1,5 +1,15 @@
 pub mod map_coordinate {
-    use std::ops::{Add, Sub};
+    use std::cmp::Ordering;
+    use std::fmt::{Display, Formatter};
+    use std::hash::{Hash, Hasher};
+    use std::ops::{Add, Neg, Sub};
+    use std::str::FromStr;
+
+    use robotics_lib::interface::Direction;
+    use robotics_lib::runner::Runnable;
+    use robotics_lib::world::coordinates::Coordinate;
+
+    use crate::errors::tool_errors::ToolError;
     /// The `MapCoordinate` struct represents coordinates within a two-dimensional map or grid.
     ///
     /// ## Fields
@@ -106,11 +116,217 @@ pub mod map_coordinate {
             self.height = height;
         }
 
+        /// Returns the Euclidean distance between the two coordinates.
+        ///
+        /// Teleports are ignored: use `PathFinder::distance` for the energy needed to walk
+        /// from one tile to the other, teleport hops included.
         pub fn get_distance(&self, other: &MapCoordinate) -> f64 {
             ((self.width as f64 - other.width as f64).powf(2.)
                 + (self.height as f64 - other.height as f64).powf(2.))
             .sqrt()
         }
+
+        /// Returns the offset leading from this coordinate to `other`.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use tile_resource_mapper_tool::coordinates::map_coordinate::{MapCoordinate, Offset};
+        /// let from = MapCoordinate::new(3, 1);
+        /// assert_eq!(from.offset_to(&MapCoordinate::new(1, 2)), Offset::new(-2, 1));
+        /// ```
+        pub fn offset_to(&self, other: &MapCoordinate) -> Offset {
+            Offset::new(
+                other.width as isize - self.width as isize,
+                other.height as isize - self.height as isize,
+            )
+        }
+
+        /// Checks whether the coordinate lies inside a map whose side is `size` tiles long.
+        pub fn is_within(&self, size: usize) -> bool {
+            self.width < size && self.height < size
+        }
+
+        /// Moves the coordinate by `offset`.
+        ///
+        /// Returns `None` if the result lies outside a map whose side is `size` tiles long.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use tile_resource_mapper_tool::coordinates::map_coordinate::{MapCoordinate, Offset};
+        /// let coordinates = MapCoordinate::new(0, 4);
+        /// assert_eq!(coordinates.checked_add(Offset::new(1, 0), 5), Some(MapCoordinate::new(1, 4)));
+        /// assert_eq!(coordinates.checked_add(Offset::new(-1, 0), 5), None);
+        /// assert_eq!(coordinates.checked_add(Offset::new(0, 1), 5), None);
+        /// ```
+        pub fn checked_add(&self, offset: Offset, size: usize) -> Option<MapCoordinate> {
+            let width = self.width.checked_add_signed(offset.width)?;
+            let height = self.height.checked_add_signed(offset.height)?;
+            Some(MapCoordinate::new(width, height)).filter(|c| c.is_within(size))
+        }
+
+        /// Moves the coordinate by the opposite of `offset`, see `checked_add`.
+        pub fn checked_sub(&self, offset: Offset, size: usize) -> Option<MapCoordinate> {
+            self.checked_add(-offset, size)
+        }
+
+        /// Moves the coordinate by `offset`, stopping at the borders of a map whose side is `size` tiles long.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use tile_resource_mapper_tool::coordinates::map_coordinate::{MapCoordinate, Offset};
+        /// let coordinates = MapCoordinate::new(1, 3);
+        /// assert_eq!(coordinates.saturating_add(Offset::new(-4, 4), 5), MapCoordinate::new(0, 4));
+        /// ```
+        pub fn saturating_add(&self, offset: Offset, size: usize) -> MapCoordinate {
+            let last = size.saturating_sub(1);
+            MapCoordinate::new(
+                self.width.saturating_add_signed(offset.width).min(last),
+                self.height.saturating_add_signed(offset.height).min(last),
+            )
+        }
+
+        /// Moves the coordinate by the opposite of `offset`, see `saturating_add`.
+        pub fn saturating_sub(&self, offset: Offset, size: usize) -> MapCoordinate {
+            self.saturating_add(-offset, size)
+        }
+
+        /// Converts the coordinate into a robotics_lib `Coordinate`, which is expressed as row and column.
+        ///
+        /// Returns an error if the coordinate lies outside a map whose side is `size` tiles long.
+        pub fn to_coordinate(&self, size: usize) -> Result<Coordinate, ToolError> {
+            if self.is_within(size) {
+                Ok(Coordinate::new(self.height, self.width))
+            } else {
+                Err(ToolError::OutOfBounds {
+                    coordinate: *self,
+                    size,
+                })
+            }
+        }
+
+        /// Converts a robotics_lib `Coordinate` into a `MapCoordinate`.
+        ///
+        /// Returns an error if it lies outside a map whose side is `size` tiles long.
+        pub fn from_coordinate(coordinate: &Coordinate, size: usize) -> Result<Self, ToolError> {
+            let result = MapCoordinate::from(coordinate);
+            if result.is_within(size) {
+                Ok(result)
+            } else {
+                Err(ToolError::OutOfBounds {
+                    coordinate: result,
+                    size,
+                })
+            }
+        }
+    }
+
+    /// The `Offset` struct represents a signed displacement on the map, such as one step in a `Direction`.
+    ///
+    /// ## Fields
+    ///
+    /// - `width`: The displacement along the columns, negative towards the left.
+    /// - `height`: The displacement along the rows, negative upwards.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use robotics_lib::interface::Direction;
+    /// use tile_resource_mapper_tool::coordinates::map_coordinate::{MapCoordinate, Offset};
+    ///
+    /// let step = Offset::from(Direction::Left);
+    /// assert_eq!(step, Offset::new(-1, 0));
+    /// assert_eq!(MapCoordinate::new(2, 2).checked_add(step, 5), Some(MapCoordinate::new(1, 2)));
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Offset {
+        width: isize,
+        height: isize,
+    }
+
+    impl Offset {
+        /// Creates a new `Offset` with the given displacements.
+        pub fn new(width: isize, height: isize) -> Self {
+            Offset { width, height }
+        }
+
+        /// Gets the displacement along the columns.
+        pub fn get_width(&self) -> isize {
+            self.width
+        }
+
+        /// Gets the displacement along the rows.
+        pub fn get_height(&self) -> isize {
+            self.height
+        }
+
+        /// Returns the number of steps needed to cover the offset without moving diagonally.
+        pub fn manhattan_length(&self) -> usize {
+            self.width.unsigned_abs() + self.height.unsigned_abs()
+        }
+
+        /// Returns the `Direction` whose single step is this offset, if any.
+        pub fn direction(&self) -> Option<Direction> {
+            match (self.width, self.height) {
+                (0, -1) => Some(Direction::Up),
+                (0, 1) => Some(Direction::Down),
+                (-1, 0) => Some(Direction::Left),
+                (1, 0) => Some(Direction::Right),
+                _ => None,
+            }
+        }
+    }
+
+    impl Display for Offset {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "({:+}, {:+})", self.width, self.height)
+        }
+    }
+
+    impl Add for Offset {
+        type Output = Self;
+        fn add(self, rhs: Self) -> Self::Output {
+            Offset::new(self.width + rhs.width, self.height + rhs.height)
+        }
+    }
+
+    impl Sub for Offset {
+        type Output = Self;
+        fn sub(self, rhs: Self) -> Self::Output {
+            Offset::new(self.width - rhs.width, self.height - rhs.height)
+        }
+    }
+
+    impl Neg for Offset {
+        type Output = Self;
+        fn neg(self) -> Self::Output {
+            Offset::new(-self.width, -self.height)
+        }
+    }
+
+    impl From<&Direction> for Offset {
+        fn from(value: &Direction) -> Self {
+            match value {
+                Direction::Up => Offset::new(0, -1),
+                Direction::Down => Offset::new(0, 1),
+                Direction::Left => Offset::new(-1, 0),
+                Direction::Right => Offset::new(1, 0),
+            }
+        }
+    }
+
+    impl From<Direction> for Offset {
+        fn from(value: Direction) -> Self {
+            Offset::from(&value)
+        }
+    }
+
+    impl From<(isize, isize)> for Offset {
+        fn from(value: (isize, isize)) -> Self {
+            Offset::new(value.0, value.1)
+        }
     }
 
     impl PartialEq for MapCoordinate {
@@ -119,6 +335,51 @@ pub mod map_coordinate {
         }
     }
 
+    impl Display for MapCoordinate {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "({}, {})", self.width, self.height)
+        }
+    }
+
+    /// Parses coordinates written either as `(width, height)` or as `width,height`.
+    impl FromStr for MapCoordinate {
+        type Err = ToolError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let error = || ToolError::Other(format!("Invalid coordinates: {}", s));
+            let inner = s.trim().trim_start_matches('(').trim_end_matches(')');
+            let (width, height) = inner.split_once(',').ok_or_else(error)?;
+            Ok(MapCoordinate {
+                width: width.trim().parse().map_err(|_| error())?,
+                height: height.trim().parse().map_err(|_| error())?,
+            })
+        }
+    }
+
+    impl Eq for MapCoordinate {}
+
+    impl Hash for MapCoordinate {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.width.hash(state);
+            self.height.hash(state);
+        }
+    }
+
+    /// Coordinates are ordered in reading order: row by row (`height`), then column by column (`width`).
+    impl Ord for MapCoordinate {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.height
+                .cmp(&other.height)
+                .then(self.width.cmp(&other.width))
+        }
+    }
+
+    impl PartialOrd for MapCoordinate {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
     impl Add for MapCoordinate {
         type Output = Self;
         fn add(self, rhs: Self) -> Self::Output {
@@ -129,6 +390,8 @@ pub mod map_coordinate {
         }
     }
 
+    /// Panics if `rhs` is larger than `self` on either axis: use `offset_to` for a signed difference,
+    /// or `checked_sub` to move a coordinate by an `Offset` without leaving the map.
     impl Sub for MapCoordinate {
         type Output = Self;
         fn sub(self, rhs: Self) -> Self::Output {
@@ -153,4 +416,78 @@ pub mod map_coordinate {
             (self.width, self.height)
         }
     }
+
+    impl From<Coordinate> for MapCoordinate {
+        fn from(value: Coordinate) -> Self {
+            Self {
+                width: value.get_col(),
+                height: value.get_row(),
+            }
+        }
+    }
+
+    impl From<&Coordinate> for MapCoordinate {
+        fn from(value: &Coordinate) -> Self {
+            Self {
+                width: value.get_col(),
+                height: value.get_row(),
+            }
+        }
+    }
+
+    /// Anything with a position on the map, used as the origin of the queries of `TileMapper`.
+    ///
+    /// Every robot is `Locatable`, and so are a `MapCoordinate`, a robotics_lib `Coordinate` and a
+    /// `(width, height)` tuple, so the same query can be run from the robot or from a hypothetical
+    /// position, e.g. the closest coin to a bank.
+    ///
+    /// The `Kind` parameter only keeps the implementation for robots apart from the others, and is
+    /// inferred by the compiler: queries take `&impl Locatable<K>` and callers never name it.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+    ///
+    /// let mapper = TileMapper{};
+    ///
+    /// let bank = mapper.find_closest(world, robot, Content::Bank(0..0))?;
+    /// let coin = mapper.find_closest(world, &bank, Content::Coin(0))?;
+    /// let rock = mapper.find_closest(world, &(3, 4), Content::Rock(0))?;
+    /// let tree = mapper.find_closest(world, robot.get_coordinate(), Content::Tree(0))?;
+    /// ```
+    pub trait Locatable<Kind = PositionKind> {
+        /// Returns the position on the map.
+        fn location(&self) -> MapCoordinate;
+    }
+
+    /// The kind of `Locatable` positions: `MapCoordinate`, `Coordinate` and `(width, height)` tuples.
+    pub enum PositionKind {}
+
+    /// The kind of `Locatable` robots, anything implementing `Runnable`.
+    pub enum RobotKind {}
+
+    impl Locatable for MapCoordinate {
+        fn location(&self) -> MapCoordinate {
+            *self
+        }
+    }
+
+    impl Locatable for Coordinate {
+        fn location(&self) -> MapCoordinate {
+            MapCoordinate::from(self)
+        }
+    }
+
+    impl Locatable for (usize, usize) {
+        fn location(&self) -> MapCoordinate {
+            MapCoordinate::from(*self)
+        }
+    }
+
+    impl<R: Runnable + ?Sized> Locatable<RobotKind> for R {
+        fn location(&self) -> MapCoordinate {
+            MapCoordinate::from(self.get_coordinate())
+        }
+    }
 }
@@ -0,0 +1,152 @@
+pub mod cost_model {
+
+    use robotics_lib::interface::look_at_sky;
+    use robotics_lib::world::environmental_conditions::{EnvironmentalConditions, WeatherType};
+    use robotics_lib::world::tile::Content;
+    use robotics_lib::world::World;
+
+    use crate::coordinates::map_coordinate::Locatable;
+    use crate::errors::tool_errors::ToolError;
+    use crate::path::path_finder::PathOptions;
+    use crate::scoring::target_scoring::{ScoredTarget, ScoringWeights};
+    use crate::tool::tile_mapper::TileMapper;
+
+    /// How much more moving costs with every kind of weather, compared to a sunny day.
+    ///
+    /// The defaults are guesses, not read from robotics_lib: they only make far targets less attractive
+    /// as the weather gets worse. Set them to 1.0 to ignore the weather.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct WeatherMultipliers {
+        pub sunny: f64,
+        pub rainy: f64,
+        pub foggy: f64,
+        pub tropical_monsoon: f64,
+        pub trentino_snow: f64,
+    }
+
+    impl Default for WeatherMultipliers {
+        fn default() -> Self {
+            WeatherMultipliers {
+                sunny: 1.,
+                rainy: 1.5,
+                foggy: 1.25,
+                tropical_monsoon: 2.,
+                trentino_snow: 1.75,
+            }
+        }
+    }
+
+    impl WeatherMultipliers {
+        /// Gets the multiplier of `weather`.
+        pub fn get(&self, weather: &WeatherType) -> f64 {
+            match weather {
+                WeatherType::Sunny => self.sunny,
+                WeatherType::Rainy => self.rainy,
+                WeatherType::Foggy => self.foggy,
+                WeatherType::TropicalMonsoon => self.tropical_monsoon,
+                WeatherType::TrentinoSnow => self.trentino_snow,
+            }
+        }
+    }
+
+    /// The `CostModel` struct scales travel costs according to the weather, so that far targets
+    /// are penalized more during rain, fog or snow.
+    ///
+    /// With a forecast, the multiplier is the mean over the current weather and the forecast,
+    /// which fits targets that take several ticks to reach.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::cost::cost_model::CostModel;
+    /// use tile_resource_mapper_tool::path::path_finder::PathOptions;
+    ///
+    /// let model = CostModel::from_conditions(&look_at_sky(world));
+    /// let path = mapper.find_path(world, self, target, model.path_options(PathOptions::default()))?;
+    /// ```
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct CostModel {
+        pub multipliers: WeatherMultipliers,
+        pub weather: WeatherType,
+        pub forecast: Vec<WeatherType>,
+    }
+
+    impl CostModel {
+        /// Creates a `CostModel` for the current weather of `conditions`, with the default multipliers.
+        pub fn from_conditions(conditions: &EnvironmentalConditions) -> Self {
+            CostModel {
+                multipliers: WeatherMultipliers::default(),
+                weather: conditions.get_weather_condition(),
+                forecast: vec![],
+            }
+        }
+
+        /// Adds the weather expected in the next ticks.
+        pub fn with_forecast(mut self, forecast: Vec<WeatherType>) -> Self {
+            self.forecast = forecast;
+            self
+        }
+
+        /// Replaces the multipliers of every kind of weather.
+        pub fn with_multipliers(mut self, multipliers: WeatherMultipliers) -> Self {
+            self.multipliers = multipliers;
+            self
+        }
+
+        /// Returns the factor applied to travel costs: the multiplier of the current weather,
+        /// averaged with the multipliers of the forecast if there is one.
+        pub fn multiplier(&self) -> f64 {
+            let total: f64 = std::iter::once(&self.weather)
+                .chain(self.forecast.iter())
+                .map(|weather| self.multipliers.get(weather))
+                .sum();
+            total / (self.forecast.len() + 1) as f64
+        }
+
+        /// Returns `options` with the weather multiplier applied to every move.
+        pub fn path_options(&self, options: PathOptions) -> PathOptions {
+            PathOptions {
+                weather_multiplier: options.weather_multiplier * self.multiplier(),
+                ..options
+            }
+        }
+
+        /// Returns `weights` with the cost of travelling scaled by the weather multiplier.
+        pub fn scoring_weights(&self, weights: &ScoringWeights) -> ScoringWeights {
+            ScoringWeights {
+                travel_weight: weights.travel_weight * self.multiplier(),
+                ..*weights
+            }
+        }
+    }
+
+    impl TileMapper {
+        /// Returns the `CostModel` of the current weather of `world`.
+        pub fn cost_model(world: &World) -> CostModel {
+            CostModel::from_conditions(&look_at_sky(world))
+        }
+
+        /// Same as `rank_targets`, but the cost of travelling is scaled by the current weather,
+        /// so that nearby targets are preferred during rain or fog.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::scoring::target_scoring::ScoringWeights;
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// let targets = mapper.rank_targets_in_weather(world, robot, Content::Tree(0), &ScoringWeights::default())?;
+        /// ```
+        pub fn rank_targets_in_weather<K>(
+            &self,
+            world: &World,
+            origin: &impl Locatable<K>,
+            content: Content,
+            weights: &ScoringWeights,
+        ) -> Result<Vec<ScoredTarget>, ToolError> {
+            let weights = TileMapper::cost_model(world).scoring_weights(weights);
+            self.rank_targets(world, origin, content, &weights)
+        }
+    }
+}
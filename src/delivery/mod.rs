@@ -0,0 +1,314 @@
+pub mod drop_off {
+    use std::collections::HashMap;
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::runner::Runnable;
+    use robotics_lib::world::tile::Content;
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{ContentNotDiscovered, Other, WorldNotDiscovered};
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{
+        content_name, stored_amount, ContentQuantity, ResourceMap, TileMapper,
+    };
+
+    /// Returns the kind of tile where `cargo` can be delivered: a `Bin` for garbage, a `Bank` for coins
+    /// and a `Market` for the contents that can be sold. Returns `None` for anything else.
+    pub fn destination_for(cargo: &Content) -> Option<Content> {
+        match cargo {
+            Content::Garbage(_) => Some(Content::Bin(0..0)),
+            Content::Coin(_) => Some(Content::Bank(0..0)),
+            Content::Rock(_) | Content::Tree(_) | Content::Fish(_) => Some(Content::Market(0)),
+            _ => None,
+        }
+    }
+
+    /// Returns how many units the tile can still accept.
+    ///
+    /// Containers accept units until the start of their range reaches the end, markets until their counter reaches 0.
+    pub(crate) fn free_capacity(quantity: &ContentQuantity) -> usize {
+        match quantity {
+            (None, Some(range)) => range.end.saturating_sub(range.start),
+            (Some(q), None) => *q,
+            (_, _) => 0,
+        }
+    }
+
+    /// A tile where the robot can deliver part of its backpack.
+    ///
+    /// ## Fields
+    ///
+    /// - `coordinate`: The coordinates of the tile.
+    /// - `destination`: The kind of the tile's content (`Bin`, `Bank` or `Market`).
+    /// - `cargo`: The kind of content delivered there.
+    /// - `amount`: How many units of `cargo` are in the backpack.
+    /// - `free_capacity`: How many units the tile can still accept.
+    /// - `deliverable`: How many units can actually be delivered, the minimum of `amount` and `free_capacity`.
+    /// - `distance`: The distance from the position the destinations were computed from.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Destination {
+        pub coordinate: MapCoordinate,
+        pub destination: Discriminant<Content>,
+        pub cargo: Discriminant<Content>,
+        pub amount: usize,
+        pub free_capacity: usize,
+        pub deliverable: usize,
+        pub distance: f64,
+    }
+
+    /// A trip collecting a resource and delivering it right after.
+    ///
+    /// ## Fields
+    ///
+    /// - `source`: The tile the resource is collected from.
+    /// - `amount`: How many units are expected to be collected and delivered.
+    /// - `destination`: Where the collected units are delivered.
+    /// - `total_distance`: The distance from the robot to `source`, plus the distance from `source` to the destination.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DeliveryPlan {
+        pub source: MapCoordinate,
+        pub amount: usize,
+        pub destination: Destination,
+        pub total_distance: f64,
+    }
+
+    /// Lists the tiles of `resources` able to accept `amount` units of `cargo`, as seen from `origin`.
+    fn destinations_for_cargo(
+        resources: &ResourceMap,
+        cargo: &Content,
+        amount: usize,
+        origin: &MapCoordinate,
+    ) -> Vec<Destination> {
+        let destination = match destination_for(cargo) {
+            Some(destination) => destination,
+            None => return vec![],
+        };
+        resources
+            .get(&discriminant(&destination))
+            .into_iter()
+            .flatten()
+            .map(|(coordinate, quantity)| {
+                let free_capacity = free_capacity(quantity);
+                Destination {
+                    coordinate: *coordinate,
+                    destination: discriminant(&destination),
+                    cargo: discriminant(cargo),
+                    amount,
+                    free_capacity,
+                    deliverable: amount.min(free_capacity),
+                    distance: coordinate.get_distance(origin),
+                }
+            })
+            .filter(|d| d.free_capacity > 0)
+            .collect()
+    }
+
+    /// Sorts destinations so that the ones able to accept everything come first, then by distance,
+    /// then by free capacity.
+    fn rank(destinations: &mut [Destination]) {
+        destinations.sort_by(|a, b| {
+            (b.deliverable == b.amount)
+                .cmp(&(a.deliverable == a.amount))
+                .then(a.distance.total_cmp(&b.distance))
+                .then(b.free_capacity.cmp(&a.free_capacity))
+                .then(a.coordinate.cmp(&b.coordinate))
+        });
+    }
+
+    impl TileMapper {
+        /// Lists where the contents of a backpack can be delivered.
+        ///
+        /// # Arguments
+        ///
+        /// * `resources` - A resource map, as returned by `collection`.
+        /// * `backpack` - The contents of the backpack, as returned by `BackPack::get_contents`.
+        /// * `origin` - The position distances are computed from.
+        ///
+        /// # Returns
+        ///
+        /// Returns the destinations of every deliverable content, best first: destinations able to accept
+        /// the whole amount come first, then the closest ones, then the ones with more free capacity.
+        /// Full destinations are left out.
+        pub fn drop_off_destinations(
+            resources: &ResourceMap,
+            backpack: &HashMap<Content, usize>,
+            origin: MapCoordinate,
+        ) -> Vec<Destination> {
+            let mut destinations: Vec<Destination> = backpack
+                .iter()
+                .filter(|(_, amount)| **amount > 0)
+                .flat_map(|(cargo, amount)| {
+                    destinations_for_cargo(resources, cargo, *amount, &origin)
+                })
+                .collect();
+            rank(&mut destinations);
+            destinations
+        }
+
+        /// Finds where the robot can deliver what is in its backpack.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `robot` - A reference to an object implementing the "Runnable" trait.
+        ///
+        /// # Returns
+        ///
+        /// Returns the possible destinations ranked as in `drop_off_destinations`, the best one first:
+        /// a `Bin` for garbage, a `Bank` for coins and a `Market` for the contents that can be sold.
+        /// Returns an error if the backpack holds nothing deliverable or no destination has been discovered.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// match mapper.find_drop_off(world, robot) {
+        ///     Ok(destinations) => println!("deliver at {}", destinations[0].coordinate),
+        ///     Err(e) => println!("{}", e)
+        /// }
+        /// ```
+        pub fn find_drop_off<S: MapSource + ?Sized>(
+            &self,
+            source: &S,
+            robot: &impl Runnable,
+        ) -> Result<Vec<Destination>, ToolError> {
+            let backpack = robot.get_backpack().get_contents();
+            // reported when no destination is found; the first kind by name, so that it does not
+            // depend on the order of the backpack
+            let wanted = match backpack
+                .iter()
+                .filter(|(_, amount)| **amount > 0)
+                .filter_map(|(cargo, _)| destination_for(cargo))
+                .min_by_key(|destination| content_name(destination))
+            {
+                Some(destination) => discriminant(&destination),
+                None => return Err(Other("The backpack holds nothing to deliver".to_string())),
+            };
+            match source.resources() {
+                Some(map) => {
+                    let robot_coordinates = robot.location();
+                    let destinations =
+                        TileMapper::drop_off_destinations(&map, backpack, robot_coordinates);
+                    if destinations.is_empty() {
+                        Err(ContentNotDiscovered(wanted))
+                    } else {
+                        Ok(destinations)
+                    }
+                }
+                None => Err(WorldNotDiscovered),
+            }
+        }
+
+        /// Plans a trip collecting `content` and delivering it, minimizing the total distance.
+        ///
+        /// # Arguments
+        ///
+        /// * `resources` - A resource map, as returned by `collection`.
+        /// * `content` - The Content to collect.
+        /// * `origin` - The position the trip starts from.
+        /// * `free_space` - How many units the backpack can still hold.
+        ///
+        /// # Returns
+        ///
+        /// Returns the shortest trip from a tile with `content` to a destination accepting it,
+        /// or `None` if there is none.
+        pub fn plan_delivery(
+            resources: &ResourceMap,
+            content: &Content,
+            origin: MapCoordinate,
+            free_space: usize,
+        ) -> Option<DeliveryPlan> {
+            let sources = resources.get(&discriminant(content))?;
+            let mut best: Option<DeliveryPlan> = None;
+            for (source, quantity) in sources.iter() {
+                let amount = stored_amount(quantity).min(free_space);
+                if amount == 0 {
+                    continue;
+                }
+                let mut destinations = destinations_for_cargo(resources, content, amount, source);
+                rank(&mut destinations);
+                if let Some(destination) = destinations.into_iter().next() {
+                    let total_distance = origin.get_distance(source) + destination.distance;
+                    if best
+                        .as_ref()
+                        .map(|plan| total_distance < plan.total_distance)
+                        .unwrap_or(true)
+                    {
+                        best = Some(DeliveryPlan {
+                            source: *source,
+                            amount: destination.deliverable,
+                            destination,
+                            total_distance,
+                        });
+                    }
+                }
+            }
+            best
+        }
+
+        /// Plans a trip collecting a certain Content and delivering it right after.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `robot` - A reference to an object implementing the "Runnable" trait.
+        /// * `content` - The Content to collect.
+        ///
+        /// # Returns
+        ///
+        /// Returns the trip minimizing the distance from the robot to the source plus the distance from
+        /// the source to the destination. The amount is limited by the free space of the backpack and
+        /// by the free capacity of the destination.
+        /// Returns an error if `content` cannot be delivered anywhere or no trip is possible.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// match mapper.plan_collect_and_deliver(world, robot, Content::Garbage(0)) {
+        ///     Ok(plan) => println!("collect at {}, deliver at {}", plan.source, plan.destination.coordinate),
+        ///     Err(e) => println!("{}", e)
+        /// }
+        /// ```
+        pub fn plan_collect_and_deliver<S: MapSource + ?Sized>(
+            &self,
+            source: &S,
+            robot: &impl Runnable,
+            content: Content,
+        ) -> Result<DeliveryPlan, ToolError> {
+            let destination = match destination_for(&content) {
+                Some(destination) => destination,
+                None => {
+                    return Err(Other(format!(
+                        "{} cannot be delivered anywhere",
+                        content_name(&content)
+                    )))
+                }
+            };
+            let backpack = robot.get_backpack();
+            let used: usize = backpack.get_contents().values().sum();
+            let free_space = backpack.get_size().saturating_sub(used);
+
+            match source.resources() {
+                Some(map) => {
+                    let robot_coordinates = robot.location();
+                    TileMapper::plan_delivery(&map, &content, robot_coordinates, free_space)
+                        .ok_or_else(|| {
+                            // either the content or a place to deliver it is missing
+                            if map.contains_key(&discriminant(&content)) {
+                                ContentNotDiscovered(discriminant(&destination))
+                            } else {
+                                ContentNotDiscovered(discriminant(&content))
+                            }
+                        })
+                }
+                None => Err(WorldNotDiscovered),
+            }
+        }
+    }
+}
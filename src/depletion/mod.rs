@@ -0,0 +1,311 @@
+pub mod depletion_tracker {
+    use std::collections::HashMap;
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::world::tile::{Content, Tile, TileType};
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{ContentNotDiscovered, WorldNotDiscovered};
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{stored_amount, TileMapper};
+
+    /// What the last observation of a tile found.
+    #[derive(Debug, Clone, Copy)]
+    struct Observation {
+        tick: usize,
+        tile_type: Discriminant<TileType>,
+        kind: Option<Discriminant<Content>>,
+        amount: usize,
+    }
+
+    /// How the quantity of one kind of content changed on the tiles of one type.
+    #[derive(Debug, Clone, Copy, Default)]
+    struct Change {
+        amount: i64,
+        ticks: usize,
+    }
+
+    impl Change {
+        fn add(&mut self, amount: i64, ticks: usize) {
+            self.amount += amount;
+            self.ticks += ticks;
+        }
+
+        fn merge(&mut self, other: &Change) {
+            self.add(other.amount, other.ticks);
+        }
+
+        fn rate(&self) -> Option<f64> {
+            (self.ticks > 0).then(|| self.amount as f64 / self.ticks as f64)
+        }
+    }
+
+    /// How many times one kind of content appeared on empty tiles of one type, and how much of it.
+    #[derive(Debug, Clone, Copy, Default)]
+    struct Appearances {
+        count: usize,
+        amount: usize,
+    }
+
+    type Key = (Discriminant<Content>, Discriminant<TileType>);
+
+    /// The `DepletionTracker` struct follows the quantity of every discovered tile across
+    /// observations, to estimate how fast every kind of content is consumed or grows back on
+    /// every tile type.
+    ///
+    /// Two rates are kept:
+    /// - the depletion rate: the change of quantity per tick of a tile holding the content,
+    ///   negative when it is consumed (a tile emptied counts as losing all its quantity);
+    /// - the appearance rate: how likely an empty tile is to get the content at every tick,
+    ///   e.g. fire or garbage showing up.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::depletion::depletion_tracker::DepletionTracker;
+    ///
+    /// let mut tracker = DepletionTracker::new();
+    ///
+    /// // inside process_tick
+    /// tracker.observe(world, tick)?;
+    /// let left = tracker.projected_quantity(&target, &Content::Tree(0), tick + 10);
+    /// ```
+    #[derive(Debug, Clone, Default)]
+    pub struct DepletionTracker {
+        observations: HashMap<MapCoordinate, Observation>,
+        depletion: HashMap<Key, Change>,
+        appearances: HashMap<Key, Appearances>,
+        // ticks spent empty by the tiles of every type, over which appearances are counted
+        empty_ticks: HashMap<Discriminant<TileType>, usize>,
+    }
+
+    impl DepletionTracker {
+        /// Creates a tracker with no observation.
+        pub fn new() -> Self {
+            DepletionTracker::default()
+        }
+
+        /// Records the tiles discovered in `source` at `tick`.
+        ///
+        /// Every tile already observed at an earlier tick updates the rates; tiles observed at
+        /// the same tick or later are only replaced.
+        ///
+        /// Returns an error if the source has no grid of tiles.
+        pub fn observe<S: MapSource + ?Sized>(
+            &mut self,
+            source: &S,
+            tick: usize,
+        ) -> Result<(), ToolError> {
+            let robot_world = source.tiles().ok_or(WorldNotDiscovered)?;
+            for (row, row_vector) in robot_world.iter().enumerate() {
+                for (column, element) in row_vector.iter().enumerate() {
+                    if let Some(tile) = element {
+                        self.observe_tile(MapCoordinate::new(column, row), tile, tick);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        fn observe_tile(&mut self, coordinate: MapCoordinate, tile: &Tile, tick: usize) {
+            let current = Observation {
+                tick,
+                tile_type: discriminant(&tile.tile_type),
+                kind: (tile.content != Content::None).then(|| discriminant(&tile.content)),
+                amount: stored_amount(&tile.content.get_value()),
+            };
+            if let Some(previous) = self.observations.insert(coordinate, current) {
+                if previous.tick >= tick {
+                    return;
+                }
+                let ticks = tick - previous.tick;
+                let tile_type = current.tile_type;
+                match (previous.kind, current.kind) {
+                    (Some(before), Some(after)) if before == after => {
+                        let change = current.amount as i64 - previous.amount as i64;
+                        self.depletion
+                            .entry((before, tile_type))
+                            .or_default()
+                            .add(change, ticks);
+                    }
+                    (before, after) => {
+                        if let Some(before) = before {
+                            self.depletion
+                                .entry((before, tile_type))
+                                .or_default()
+                                .add(-(previous.amount as i64), ticks);
+                        } else {
+                            *self.empty_ticks.entry(tile_type).or_default() += ticks;
+                        }
+                        if let Some(after) = after {
+                            let appearances =
+                                self.appearances.entry((after, tile_type)).or_default();
+                            appearances.count += 1;
+                            appearances.amount += current.amount;
+                        }
+                    }
+                }
+            }
+        }
+
+        /// Returns the change of quantity per tick of a tile holding the same kind of `content`,
+        /// over every tile type, or `None` if no such tile was observed twice.
+        pub fn depletion_rate(&self, content: &Content) -> Option<f64> {
+            let kind = discriminant(content);
+            let mut total = Change::default();
+            self.depletion
+                .iter()
+                .filter(|((k, _), _)| *k == kind)
+                .for_each(|(_, change)| total.merge(change));
+            total.rate()
+        }
+
+        /// Same as `depletion_rate`, for tiles of type `tile_type` only.
+        pub fn depletion_rate_on(&self, content: &Content, tile_type: &TileType) -> Option<f64> {
+            self.depletion
+                .get(&(discriminant(content), discriminant(tile_type)))
+                .and_then(Change::rate)
+        }
+
+        /// Returns the probability per tick that an empty tile of type `tile_type` gets the same
+        /// kind of `content`, or `None` if no empty tile of that type was observed twice.
+        pub fn appearance_rate(&self, content: &Content, tile_type: &TileType) -> Option<f64> {
+            let ticks = *self.empty_ticks.get(&discriminant(tile_type))?;
+            if ticks == 0 {
+                return None;
+            }
+            let appeared = self
+                .appearances
+                .get(&(discriminant(content), discriminant(tile_type)))
+                .map_or(0, |appearances| appearances.count);
+            Some(appeared as f64 / ticks as f64)
+        }
+
+        /// Projects the quantity of the same kind of `content` on the tile at `coordinate`
+        /// at a future `tick`, from its last observation.
+        ///
+        /// For a tile holding the content, its last quantity plus the depletion rate of its tile
+        /// type (or of every tile type, if the type has no data) times the elapsed ticks, never
+        /// below 0. For an empty tile, the expected quantity brought by appearances.
+        /// Returns `None` if the tile was never observed.
+        pub fn projected_quantity(
+            &self,
+            coordinate: &MapCoordinate,
+            content: &Content,
+            tick: usize,
+        ) -> Option<f64> {
+            let observation = self.observations.get(coordinate)?;
+            let kind = discriminant(content);
+            let ticks = tick.saturating_sub(observation.tick) as f64;
+            match observation.kind {
+                Some(held) if held == kind => {
+                    let rate = self
+                        .depletion
+                        .get(&(kind, observation.tile_type))
+                        .and_then(Change::rate)
+                        .or_else(|| self.depletion_rate(content))
+                        .unwrap_or(0.);
+                    Some((observation.amount as f64 + rate * ticks).max(0.))
+                }
+                Some(_) => Some(0.),
+                None => {
+                    let key = (kind, observation.tile_type);
+                    let ticks_empty = self
+                        .empty_ticks
+                        .get(&observation.tile_type)
+                        .copied()
+                        .unwrap_or(0);
+                    let appeared = self.appearances.get(&key).filter(|_| ticks_empty > 0);
+                    Some(appeared.map_or(0., |appearances| {
+                        let probability =
+                            (appearances.count as f64 * ticks / ticks_empty as f64).min(1.);
+                        probability * appearances.amount as f64 / appearances.count as f64
+                    }))
+                }
+            }
+        }
+
+        /// Checks whether the tile at `coordinate` is expected to hold less than one unit of
+        /// `content` at `tick`. Tiles never observed are assumed empty.
+        pub fn likely_empty(
+            &self,
+            coordinate: &MapCoordinate,
+            content: &Content,
+            tick: usize,
+        ) -> bool {
+            self.projected_quantity(coordinate, content, tick)
+                .is_none_or(|quantity| quantity < 1.)
+        }
+    }
+
+    impl TileMapper {
+        /// Same as `find_closest`, but skips the tiles likely to be empty by the time the robot
+        /// gets there, according to `tracker`.
+        ///
+        /// Only contents with an amount, like trees or rocks, are projected: tiles holding the
+        /// others, like fire or containers, are never skipped.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `origin` - The position distances are measured from, e.g. the robot.
+        /// * `content` - The Content we are looking for.
+        /// * `tracker` - The tracker projecting the quantity of every tile.
+        /// * `tick` - The current tick; the robot is assumed to move one tile per tick.
+        ///
+        /// # Returns
+        ///
+        /// Returns the Map coordinates of the closest tile expected to still hold `content` on arrival.
+        /// Returns an error if it fails to find a tile.
+        ///
+        /// # Example
+        /// ```ignore
+        /// let tree = mapper.find_closest_lasting(world, robot, Content::Tree(0), &tracker, tick)?;
+        /// ```
+        pub fn find_closest_lasting<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            content: Content,
+            tracker: &DepletionTracker,
+            tick: usize,
+        ) -> Result<MapCoordinate, ToolError> {
+            let origin_coordinates = origin.location();
+            let mut closest: Option<(MapCoordinate, f64)> = None;
+            let discovered = source.visit(&content, &mut |entry| {
+                let coordinates = entry.coordinate();
+                let arrival = tick
+                    + origin_coordinates
+                        .offset_to(&coordinates)
+                        .manhattan_length();
+                // tiles the tracker has not observed yet are judged on what they hold now
+                let lasting = match entry.quantity() {
+                    (Some(amount), _) => tracker
+                        .projected_quantity(&coordinates, &content, arrival)
+                        .map_or(amount > 0, |quantity| quantity >= 1.),
+                    _ => true,
+                };
+                if !lasting {
+                    return;
+                }
+                let distance = coordinates.get_distance(&origin_coordinates);
+                closest = match closest {
+                    Some((old, old_distance))
+                        if old_distance < distance
+                            || (old_distance == distance && old < coordinates) =>
+                    {
+                        Some((old, old_distance))
+                    }
+                    _ => Some((coordinates, distance)),
+                };
+            });
+            if !discovered {
+                return Err(WorldNotDiscovered);
+            }
+            closest
+                .map(|(coordinates, _)| coordinates)
+                .ok_or(ContentNotDiscovered(discriminant(&content)))
+        }
+    }
+}
@@ -0,0 +1,356 @@
+pub mod resource_diff {
+    use std::collections::{HashMap, HashSet};
+    use std::fmt::{Display, Formatter};
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::world::tile::Content;
+
+    use crate::coordinates::map_coordinate::MapCoordinate;
+    use crate::tool::tile_mapper::{
+        format_quantity, kind_name, ContentQuantity, ResourceMap, TileMapper,
+    };
+
+    /// What `ResourceDiff::apply_to` does to a tile once every entry is taken into account.
+    enum Edit {
+        // adds the tile or replaces its quantity
+        Set(ContentQuantity),
+        // replaces the quantity of the tile, if it is listed
+        Update(ContentQuantity),
+        Remove,
+    }
+
+    /// A single difference between two resource maps.
+    ///
+    /// ## Variants
+    ///
+    /// - `Added`: the tile was not listed for `kind` in the old map and is listed in the new one.
+    /// - `Removed`: the tile was listed for `kind` in the old map and is not listed in the new one.
+    /// - `Changed`: the tile is listed for `kind` in both maps, with a different quantity.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum DiffEntry {
+        Added {
+            kind: Discriminant<Content>,
+            coordinate: MapCoordinate,
+            quantity: ContentQuantity,
+        },
+        Removed {
+            kind: Discriminant<Content>,
+            coordinate: MapCoordinate,
+            quantity: ContentQuantity,
+        },
+        Changed {
+            kind: Discriminant<Content>,
+            coordinate: MapCoordinate,
+            before: ContentQuantity,
+            after: ContentQuantity,
+        },
+    }
+
+    impl DiffEntry {
+        /// Gets the kind of `Content` the entry refers to.
+        pub fn kind(&self) -> Discriminant<Content> {
+            match self {
+                DiffEntry::Added { kind, .. }
+                | DiffEntry::Removed { kind, .. }
+                | DiffEntry::Changed { kind, .. } => *kind,
+            }
+        }
+
+        /// Gets the coordinates of the tile the entry refers to.
+        pub fn coordinate(&self) -> MapCoordinate {
+            match self {
+                DiffEntry::Added { coordinate, .. }
+                | DiffEntry::Removed { coordinate, .. }
+                | DiffEntry::Changed { coordinate, .. } => *coordinate,
+            }
+        }
+
+        /// Position of the variant when sorting, so that for the same tile removals come first.
+        fn rank(&self) -> u8 {
+            match self {
+                DiffEntry::Removed { .. } => 0,
+                DiffEntry::Changed { .. } => 1,
+                DiffEntry::Added { .. } => 2,
+            }
+        }
+    }
+
+    impl Display for DiffEntry {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            match self {
+                DiffEntry::Added {
+                    kind,
+                    coordinate,
+                    quantity,
+                } => write!(
+                    f,
+                    "+ {} at {}: {}",
+                    kind_name(kind),
+                    coordinate,
+                    format_quantity(quantity)
+                ),
+                DiffEntry::Removed {
+                    kind,
+                    coordinate,
+                    quantity,
+                } => write!(
+                    f,
+                    "- {} at {}: {}",
+                    kind_name(kind),
+                    coordinate,
+                    format_quantity(quantity)
+                ),
+                DiffEntry::Changed {
+                    kind,
+                    coordinate,
+                    before,
+                    after,
+                } => write!(
+                    f,
+                    "~ {} at {}: {} -> {}",
+                    kind_name(kind),
+                    coordinate,
+                    format_quantity(before),
+                    format_quantity(after)
+                ),
+            }
+        }
+    }
+
+    /// The `ResourceDiff` struct lists everything that changed between two resource maps,
+    /// e.g. the results of `collection` at two different ticks.
+    ///
+    /// Entries are sorted by content name, then by coordinates in reading order.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+    ///
+    /// let before = TileMapper::collection(world).unwrap_or_default();
+    /// // ... some ticks later
+    /// let after = TileMapper::collection(world).unwrap_or_default();
+    ///
+    /// let diff = TileMapper::diff(&before, &after);
+    /// println!("{}", diff.summary());
+    /// ```
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct ResourceDiff {
+        entries: Vec<DiffEntry>,
+    }
+
+    impl ResourceDiff {
+        /// Computes the differences needed to go from `before` to `after`.
+        pub fn between(before: &ResourceMap, after: &ResourceMap) -> Self {
+            let mut entries = vec![];
+            let kinds: HashSet<&Discriminant<Content>> =
+                before.keys().chain(after.keys()).collect();
+
+            for kind in kinds {
+                let old: HashMap<MapCoordinate, &ContentQuantity> = before
+                    .get(kind)
+                    .map(|v| v.iter().map(|(c, q)| (*c, q)).collect())
+                    .unwrap_or_default();
+                let new: HashMap<MapCoordinate, &ContentQuantity> = after
+                    .get(kind)
+                    .map(|v| v.iter().map(|(c, q)| (*c, q)).collect())
+                    .unwrap_or_default();
+
+                for (coordinate, quantity) in old.iter() {
+                    match new.get(coordinate) {
+                        None => entries.push(DiffEntry::Removed {
+                            kind: *kind,
+                            coordinate: *coordinate,
+                            quantity: (*quantity).clone(),
+                        }),
+                        Some(new_quantity) if new_quantity != quantity => {
+                            entries.push(DiffEntry::Changed {
+                                kind: *kind,
+                                coordinate: *coordinate,
+                                before: (*quantity).clone(),
+                                after: (*new_quantity).clone(),
+                            })
+                        }
+                        Some(_) => {}
+                    }
+                }
+                for (coordinate, quantity) in new.iter() {
+                    if !old.contains_key(coordinate) {
+                        entries.push(DiffEntry::Added {
+                            kind: *kind,
+                            coordinate: *coordinate,
+                            quantity: (*quantity).clone(),
+                        });
+                    }
+                }
+            }
+
+            entries.sort_by(|a, b| {
+                kind_name(&a.kind())
+                    .cmp(kind_name(&b.kind()))
+                    .then(a.coordinate().cmp(&b.coordinate()))
+                    .then(a.rank().cmp(&b.rank()))
+            });
+            ResourceDiff { entries }
+        }
+
+        /// Builds a diff from already computed entries, keeping their order.
+        pub(crate) fn from_entries(entries: Vec<DiffEntry>) -> Self {
+            ResourceDiff { entries }
+        }
+
+        /// Applies the diff to `map`, so that a map equal to `before` becomes equal to `after`.
+        ///
+        /// Tiles are kept in reading order, as `collection` returns them, and kinds left
+        /// without tiles are removed from the map.
+        pub fn apply_to(&self, map: &mut ResourceMap) {
+            // the net edit of every tile, so that each kind is walked and sorted once
+            let mut edits: HashMap<Discriminant<Content>, HashMap<MapCoordinate, Edit>> =
+                HashMap::new();
+            for entry in self.entries.iter() {
+                let tiles = edits.entry(entry.kind()).or_default();
+                let edit = match entry {
+                    DiffEntry::Added { quantity, .. } => Edit::Set(quantity.clone()),
+                    DiffEntry::Removed { .. } => Edit::Remove,
+                    DiffEntry::Changed { after, .. } => match tiles.get(&entry.coordinate()) {
+                        Some(Edit::Set(_)) => Edit::Set(after.clone()),
+                        Some(Edit::Remove) => Edit::Remove,
+                        _ => Edit::Update(after.clone()),
+                    },
+                };
+                tiles.insert(entry.coordinate(), edit);
+            }
+            for (kind, mut kind_edits) in edits {
+                let tiles = map.entry(kind).or_default();
+                tiles.retain_mut(
+                    |(coordinate, quantity)| match kind_edits.remove(coordinate) {
+                        Some(Edit::Set(new)) | Some(Edit::Update(new)) => {
+                            *quantity = new;
+                            true
+                        }
+                        Some(Edit::Remove) => false,
+                        None => true,
+                    },
+                );
+                tiles.extend(
+                    kind_edits
+                        .into_iter()
+                        .filter_map(|(coordinate, edit)| match edit {
+                            Edit::Set(quantity) => Some((coordinate, quantity)),
+                            _ => None,
+                        }),
+                );
+                tiles.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+            map.retain(|_, tiles| !tiles.is_empty());
+        }
+
+        /// Returns every entry of the diff.
+        pub fn entries(&self) -> &[DiffEntry] {
+            &self.entries
+        }
+
+        /// Returns `true` if the two maps were identical.
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        /// Returns the number of entries of the diff.
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        /// Iterates over the tiles that appeared.
+        pub fn added(&self) -> impl Iterator<Item = &DiffEntry> {
+            self.entries
+                .iter()
+                .filter(|e| matches!(e, DiffEntry::Added { .. }))
+        }
+
+        /// Iterates over the tiles that disappeared.
+        pub fn removed(&self) -> impl Iterator<Item = &DiffEntry> {
+            self.entries
+                .iter()
+                .filter(|e| matches!(e, DiffEntry::Removed { .. }))
+        }
+
+        /// Iterates over the tiles whose quantity changed.
+        pub fn changed(&self) -> impl Iterator<Item = &DiffEntry> {
+            self.entries
+                .iter()
+                .filter(|e| matches!(e, DiffEntry::Changed { .. }))
+        }
+
+        /// Iterates over the entries regarding the same kind of `content`.
+        pub fn for_content(&self, content: &Content) -> impl Iterator<Item = &DiffEntry> {
+            let kind = discriminant(content);
+            self.entries.iter().filter(move |e| e.kind() == kind)
+        }
+
+        /// Returns a short human-readable summary with one line per content kind, e.g.
+        /// `Rock: 1 added, 2 removed, 0 changed`.
+        pub fn summary(&self) -> String {
+            if self.entries.is_empty() {
+                return "No changes".to_string();
+            }
+            // entries are sorted by name, so the kinds are grouped together
+            let mut lines: Vec<(&'static str, [usize; 3])> = vec![];
+            for entry in self.entries.iter() {
+                let name = kind_name(&entry.kind());
+                if lines.last().map(|(n, _)| *n != name).unwrap_or(true) {
+                    lines.push((name, [0; 3]));
+                }
+                if let Some((_, counters)) = lines.last_mut() {
+                    match entry {
+                        DiffEntry::Added { .. } => counters[0] += 1,
+                        DiffEntry::Removed { .. } => counters[1] += 1,
+                        DiffEntry::Changed { .. } => counters[2] += 1,
+                    }
+                }
+            }
+            lines
+                .iter()
+                .map(|(name, c)| {
+                    format!(
+                        "{}: {} added, {} removed, {} changed",
+                        name, c[0], c[1], c[2]
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+    }
+
+    impl Display for ResourceDiff {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            writeln!(f, "{}", self.summary())?;
+            for entry in self.entries.iter() {
+                writeln!(f, "{}", entry)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl TileMapper {
+        /// Compares two results of `collection` and returns what appeared, disappeared or changed quantity.
+        ///
+        /// # Arguments
+        ///
+        /// * `before` - The older resource map.
+        /// * `after` - The newer resource map.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let diff = TileMapper::diff(&map_at_tick_100, &map_at_tick_200);
+        ///
+        /// for entry in diff.added() {
+        ///     println!("{}", entry);
+        /// }
+        /// ```
+        pub fn diff(before: &ResourceMap, after: &ResourceMap) -> ResourceDiff {
+            ResourceDiff::between(before, after)
+        }
+    }
+}
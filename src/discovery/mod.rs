@@ -0,0 +1,182 @@
+pub mod discovery_mask {
+    use robotics_lib::world::tile::Tile;
+
+    use crate::coordinates::map_coordinate::MapCoordinate;
+    use crate::region::map_region::Rect;
+
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    /// The `DiscoveryMask` struct stores one bit per tile of the map, set once the tile has
+    /// been discovered, so that checking a tile or measuring the coverage of the map does not
+    /// need the grid of tiles.
+    ///
+    /// Discovery never goes back: tiles stay discovered until the mask is cleared.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::discovery::discovery_mask::DiscoveryMask;
+    ///
+    /// let mask = DiscoveryMask::from_tiles(&robot_map(world).unwrap());
+    /// println!("{:.1}% of the world discovered", mask.coverage());
+    ///
+    /// let frontier: Vec<MapCoordinate> = mask.frontier().collect();
+    /// ```
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct DiscoveryMask {
+        size: usize,
+        words: Vec<u64>,
+        discovered: usize,
+    }
+
+    impl DiscoveryMask {
+        /// Creates a mask for a map whose side is `size` tiles long, with no tile discovered.
+        pub fn new(size: usize) -> Self {
+            DiscoveryMask {
+                size,
+                words: vec![0; (size * size).div_ceil(WORD_BITS)],
+                discovered: 0,
+            }
+        }
+
+        /// Creates the mask of a grid such as the one returned by `robot_map`.
+        pub fn from_tiles(robot_world: &[Vec<Option<Tile>>]) -> Self {
+            let mut mask = DiscoveryMask::new(robot_world.len());
+            mask.update(robot_world);
+            mask
+        }
+
+        /// Gets the side of the map, in tiles.
+        pub fn size(&self) -> usize {
+            self.size
+        }
+
+        /// Gets the number of discovered tiles.
+        pub fn discovered_count(&self) -> usize {
+            self.discovered
+        }
+
+        /// Checks whether the tile at `coordinate` has been discovered.
+        ///
+        /// Tiles outside the map are never discovered.
+        pub fn is_discovered(&self, coordinate: &MapCoordinate) -> bool {
+            match self.bit_of(coordinate) {
+                Some((word, bit)) => self.words[word] & bit != 0,
+                None => false,
+            }
+        }
+
+        /// Marks the tile at `coordinate` as discovered.
+        ///
+        /// Returns `true` if it was not discovered before, `false` if it was or if it lies
+        /// outside the map.
+        pub fn discover(&mut self, coordinate: &MapCoordinate) -> bool {
+            match self.bit_of(coordinate) {
+                Some((word, bit)) if self.words[word] & bit == 0 => {
+                    self.words[word] |= bit;
+                    self.discovered += 1;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        /// Marks every tile discovered in `robot_world` as discovered, returning how many were new.
+        ///
+        /// A grid of a different size belongs to another world, so the mask is cleared first.
+        pub fn update(&mut self, robot_world: &[Vec<Option<Tile>>]) -> usize {
+            if robot_world.len() != self.size {
+                *self = DiscoveryMask::new(robot_world.len());
+            }
+            let mut new = 0;
+            for (row, row_vector) in robot_world.iter().enumerate() {
+                for (column, element) in row_vector.iter().enumerate() {
+                    if element.is_some() && self.discover(&MapCoordinate::new(column, row)) {
+                        new += 1;
+                    }
+                }
+            }
+            new
+        }
+
+        /// Marks every tile as undiscovered.
+        pub fn clear(&mut self) {
+            self.words.iter_mut().for_each(|word| *word = 0);
+            self.discovered = 0;
+        }
+
+        /// Returns the percentage of the map discovered so far, from 0 to 100.
+        ///
+        /// An empty map counts as fully discovered.
+        pub fn coverage(&self) -> f64 {
+            percentage(self.discovered, self.size * self.size)
+        }
+
+        /// Returns the percentage of `region` discovered so far, from 0 to 100.
+        ///
+        /// The region is clipped to the map first; a region outside the map counts as fully discovered.
+        pub fn region_coverage(&self, region: &Rect) -> f64 {
+            match region.clip(self.size) {
+                Some(region) => percentage(
+                    region.iter().filter(|c| self.is_discovered(c)).count(),
+                    region.area(),
+                ),
+                None => 100.,
+            }
+        }
+
+        /// Splits the map into squares of `cell` tiles per side (the last ones may be smaller)
+        /// and returns the squares where no tile has been discovered, in reading order.
+        pub fn unexplored_regions(&self, cell: usize) -> Vec<Rect> {
+            let cell = cell.max(1);
+            let mut regions = vec![];
+            for top in (0..self.size).step_by(cell) {
+                for left in (0..self.size).step_by(cell) {
+                    let region = Rect::new(
+                        MapCoordinate::new(left, top),
+                        MapCoordinate::new(
+                            (left + cell).min(self.size) - 1,
+                            (top + cell).min(self.size) - 1,
+                        ),
+                    );
+                    if !region.iter().any(|c| self.is_discovered(&c)) {
+                        regions.push(region);
+                    }
+                }
+            }
+            regions
+        }
+
+        /// Iterates over the frontier of the discovered area, in reading order: the discovered
+        /// tiles sharing a side with an undiscovered tile of the map.
+        ///
+        /// Exploring from the frontier is the cheapest way to discover new tiles.
+        pub fn frontier(&self) -> impl Iterator<Item = MapCoordinate> + '_ {
+            Rect::whole(self.size)
+                .into_iter()
+                .flat_map(|map| map.iter())
+                .filter(move |c| {
+                    self.is_discovered(c)
+                        && c.neighbours(self.size).any(|n| !self.is_discovered(&n))
+                })
+        }
+
+        /// Returns the word holding the bit of `coordinate` and the mask of that bit.
+        fn bit_of(&self, coordinate: &MapCoordinate) -> Option<(usize, u64)> {
+            if !coordinate.is_within(self.size) {
+                return None;
+            }
+            let index = coordinate.get_height() * self.size + coordinate.get_width();
+            Some((index / WORD_BITS, 1 << (index % WORD_BITS)))
+        }
+    }
+
+    /// Returns `part` as a percentage of `total`, 100 if `total` is 0.
+    fn percentage(part: usize, total: usize) -> f64 {
+        if total == 0 {
+            100.
+        } else {
+            part as f64 * 100. / total as f64
+        }
+    }
+}
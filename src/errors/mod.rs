@@ -1,10 +1,55 @@
 pub mod tool_errors {
     use std::error::Error;
     use std::fmt::{Debug, Display, Formatter};
+    use std::io;
+    use std::mem::Discriminant;
+    use std::path::PathBuf;
 
+    use robotics_lib::utils::LibError;
+    use robotics_lib::world::tile::Content;
+
+    use crate::coordinates::map_coordinate::MapCoordinate;
+    use crate::tool::tile_mapper::kind_name;
+
+    /// The errors returned by the queries of the tool.
+    ///
+    /// ## Variants
+    ///
+    /// - `WorldNotDiscovered`: the robot has not discovered any tile yet.
+    /// - `ContentNotDiscovered`: no discovered tile holds the given kind of `Content`.
+    /// - `Unreachable`: no path leads to the given tile.
+    /// - `ContentUnreachable`: tiles with the given kind of `Content` were discovered, but none can be reached.
+    /// - `OutOfBounds`: the coordinate lies outside a map whose side is `size` tiles long.
+    /// - `InsufficientEnergy`: an action needs `required` energy, but only `available` is left.
+    /// - `GoalUnmet`: a plan cannot meet `goal`, described as in "gather 20 Tree", for the given reason.
+    /// - `CorruptSave`: a saved file could not be read; `line` is the 1-based line at fault, if known.
+    /// - `Io`: reading or writing a file failed.
+    /// - `Lib`: an action of robotics_lib failed.
+    /// - `Other`: any other error, described by its message.
     pub enum ToolError {
         WorldNotDiscovered,
-        ContentNotDiscovered,
+        ContentNotDiscovered(Discriminant<Content>),
+        Unreachable(MapCoordinate),
+        ContentUnreachable(Discriminant<Content>),
+        OutOfBounds {
+            coordinate: MapCoordinate,
+            size: usize,
+        },
+        InsufficientEnergy {
+            required: usize,
+            available: usize,
+        },
+        GoalUnmet {
+            goal: String,
+            reason: Box<ToolError>,
+        },
+        CorruptSave {
+            path: PathBuf,
+            line: Option<usize>,
+            source: Box<dyn Error + Send + Sync>,
+        },
+        Io(io::Error),
+        Lib(LibError),
         Other(String),
     }
 
@@ -20,13 +65,62 @@ pub mod tool_errors {
                 ToolError::WorldNotDiscovered => {
                     write!(f, "{}", "World not discovered yet".to_string())
                 }
-                ToolError::ContentNotDiscovered => {
-                    write!(f, "{}", "Content not discovered yet".to_string())
+                ToolError::ContentNotDiscovered(kind) => {
+                    write!(f, "{} not discovered yet", kind_name(kind))
+                }
+                ToolError::Unreachable(target) => write!(f, "No path to {}", target),
+                ToolError::ContentUnreachable(kind) => {
+                    write!(f, "No reachable tile with {}", kind_name(kind))
                 }
+                ToolError::OutOfBounds { coordinate, size } => {
+                    write!(f, "{} is outside the {}x{} map", coordinate, size, size)
+                }
+                ToolError::InsufficientEnergy {
+                    required,
+                    available,
+                } => write!(
+                    f,
+                    "Not enough energy: {} needed, {} available",
+                    required, available
+                ),
+                ToolError::GoalUnmet { goal, reason } => write!(f, "Cannot {}: {}", goal, reason),
+                ToolError::CorruptSave {
+                    path,
+                    line: Some(line),
+                    source,
+                } => write!(f, "Corrupt save {}:{}: {}", path.display(), line, source),
+                ToolError::CorruptSave {
+                    path,
+                    line: None,
+                    source,
+                } => write!(f, "Corrupt save {}: {}", path.display(), source),
+                ToolError::Io(e) => write!(f, "{}", e),
+                ToolError::Lib(e) => write!(f, "robotics_lib error: {:?}", e),
                 ToolError::Other(message) => write!(f, "{}", message),
             };
         }
     }
 
-    impl Error for ToolError {}
+    impl Error for ToolError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                ToolError::GoalUnmet { reason, .. } => Some(reason.as_ref()),
+                ToolError::CorruptSave { source, .. } => Some(source.as_ref()),
+                ToolError::Io(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<io::Error> for ToolError {
+        fn from(value: io::Error) -> Self {
+            ToolError::Io(value)
+        }
+    }
+
+    impl From<LibError> for ToolError {
+        fn from(value: LibError) -> Self {
+            ToolError::Lib(value)
+        }
+    }
 }
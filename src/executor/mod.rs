@@ -0,0 +1,419 @@
+pub mod plan_executor {
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::interface::{destroy, go, put, teleport, Direction};
+    use robotics_lib::runner::Runnable;
+    use robotics_lib::utils::LibError;
+    use robotics_lib::world::tile::Content;
+    use robotics_lib::world::World;
+
+    use crate::coordinates::map_coordinate::{MapCoordinate, Offset};
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{GoalUnmet, InsufficientEnergy, Other};
+    use crate::goals::goal_solver::{Action, ActionPlan, Constraints, EndLocation, Goal};
+    use crate::index::resource_index::ResourceIndex;
+    use crate::path::path_finder::{PathOptions, PathStep};
+    use crate::tool::tile_mapper::TileMapper;
+
+    /// The number of times a plan is recomputed after a failure before the executor gives up,
+    /// used by `PlanExecutor::new`.
+    pub const DEFAULT_MAX_REPLANS: usize = 10;
+
+    /// The energy level of a fully charged robot, as in `Robot::new`.
+    ///
+    /// robotics_lib keeps its own maximum private, so this is a copy of its value in robotics_lib 0.1.21;
+    /// the tests compare it with the energy of a new robot, so that an upgrade changing it is noticed.
+    pub const MAX_ENERGY_LEVEL: usize = 1000;
+
+    /// A single call to robotics_lib.
+    #[derive(Debug, Clone, PartialEq)]
+    enum Step {
+        Go(Direction),
+        Teleport(MapCoordinate),
+        Destroy {
+            direction: Direction,
+            amount: usize,
+        },
+        Put {
+            direction: Direction,
+            content: Content,
+            amount: usize,
+        },
+    }
+
+    /// How a step went.
+    enum Outcome {
+        Done,
+        // the step worked, but not as planned: the rest of the plan is stale
+        Stale,
+        Failed(LibError),
+    }
+
+    /// What the executor did during a call to `step`.
+    ///
+    /// ## Variants
+    ///
+    /// - `Running`: the planned actions went as expected, some are left.
+    /// - `Waiting`: the robot has not enough energy to go on; the executor retries at the next call,
+    ///   once the robot recharged. It never waits for more than `MAX_ENERGY_LEVEL`.
+    /// - `Replanning`: an action failed or did not go as planned; a new plan is computed at the next call.
+    /// - `Done`: every goal is met.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ExecutionStatus {
+        Running,
+        Waiting,
+        Replanning,
+        Done,
+    }
+
+    /// The `PlanExecutor` struct drives a robot through the plan of `solve_goals`, a few calls to
+    /// robotics_lib at every tick.
+    ///
+    /// Every result of robotics_lib is checked against the plan: when a tile is blocked, the content
+    /// is missing or the robot collects or delivers less than expected, the index is updated and the
+    /// plan is computed again from the position, the energy and the backpack of the robot.
+    /// The tiles a `go` could not enter are avoided by the new plans.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::executor::plan_executor::{ExecutionStatus, PlanExecutor};
+    ///
+    /// let mut executor = PlanExecutor::new(vec![Goal::Gather { content: Content::Tree(0), amount: 20 }])
+    ///     .ending(EndLocation::Near(Content::Bank(0..0)));
+    ///
+    /// // inside process_tick
+    /// match executor.step(self, world, &mut self.index, tick) {
+    ///     Ok(ExecutionStatus::Done) => println!("all goals met"),
+    ///     Ok(_) => {}
+    ///     Err(e) => println!("{}", e)
+    /// }
+    /// ```
+    #[derive(Debug)]
+    pub struct PlanExecutor {
+        goals: Vec<Goal>,
+        end: EndLocation,
+        path_options: PathOptions,
+        steps_per_tick: usize,
+        max_replans: usize,
+        plan: Option<ActionPlan>,
+        pending: VecDeque<Step>,
+        delivered: HashMap<Discriminant<Content>, usize>,
+        replans: usize,
+        last_failure: Option<LibError>,
+        blocked: HashSet<MapCoordinate>,
+        // the energy the robot must recharge to before planning again
+        waiting_for: Option<usize>,
+    }
+
+    impl PlanExecutor {
+        /// Creates an executor meeting `goals`, performing one step per tick and ending anywhere.
+        pub fn new(goals: Vec<Goal>) -> Self {
+            PlanExecutor {
+                goals,
+                end: EndLocation::Anywhere,
+                path_options: PathOptions::default(),
+                steps_per_tick: 1,
+                max_replans: DEFAULT_MAX_REPLANS,
+                plan: None,
+                pending: VecDeque::new(),
+                delivered: HashMap::new(),
+                replans: 0,
+                last_failure: None,
+                blocked: HashSet::new(),
+                waiting_for: None,
+            }
+        }
+
+        /// Sets where the robot must be at the end.
+        pub fn ending(mut self, end: EndLocation) -> Self {
+            self.end = end;
+            self
+        }
+
+        /// Sets how paths are computed.
+        pub fn with_path_options(mut self, path_options: PathOptions) -> Self {
+            self.path_options = path_options;
+            self
+        }
+
+        /// Sets how many calls to robotics_lib are made at every `step`, at least 1.
+        pub fn with_steps_per_tick(mut self, steps: usize) -> Self {
+            self.steps_per_tick = steps.max(1);
+            self
+        }
+
+        /// Sets how many times the plan can be recomputed after a failure.
+        pub fn with_max_replans(mut self, replans: usize) -> Self {
+            self.max_replans = replans;
+            self
+        }
+
+        /// Gets the plan being executed, if any.
+        pub fn plan(&self) -> Option<&ActionPlan> {
+            self.plan.as_ref()
+        }
+
+        /// Gets the number of calls to robotics_lib left in the current plan.
+        pub fn remaining_steps(&self) -> usize {
+            self.pending.len()
+        }
+
+        /// Gets the number of times the plan was recomputed after a failure.
+        pub fn replans(&self) -> usize {
+            self.replans
+        }
+
+        /// Gets the last error returned by robotics_lib, if any.
+        pub fn last_failure(&self) -> Option<&LibError> {
+            self.last_failure.as_ref()
+        }
+
+        /// Gets the tiles a `go` could not enter, avoided by the new plans.
+        pub fn blocked(&self) -> &HashSet<MapCoordinate> {
+            &self.blocked
+        }
+
+        /// Gets the units of the same kind of `content` delivered so far.
+        pub fn delivered(&self, content: &Content) -> usize {
+            self.delivered
+                .get(&discriminant(content))
+                .copied()
+                .unwrap_or(0)
+        }
+
+        /// Returns the goals left: the deliveries are reduced by the units already delivered.
+        pub fn remaining_goals(&self) -> Vec<Goal> {
+            let mut delivered = self.delivered.clone();
+            self.goals
+                .iter()
+                .filter_map(|goal| match goal {
+                    Goal::Deliver { content, amount } => {
+                        let done = delivered.entry(discriminant(content)).or_default();
+                        let used = (*done).min(*amount);
+                        *done -= used;
+                        (*amount > used).then(|| Goal::Deliver {
+                            content: content.clone(),
+                            amount: amount - used,
+                        })
+                    }
+                    goal => Some(goal.clone()),
+                })
+                .collect()
+        }
+
+        /// Advances the robot along the plan; meant to be called once per `process_tick`.
+        ///
+        /// # Arguments
+        ///
+        /// * `robot` - The robot performing the actions.
+        /// * `world` - The world of the robot.
+        /// * `index` - The index the plan is computed from; it is updated from the world first.
+        /// * `tick` - The current tick, recorded by the index.
+        ///
+        /// # Returns
+        ///
+        /// Returns what the executor did, see `ExecutionStatus`.
+        /// Returns an error if the goals cannot be met for another reason than energy, if they need
+        /// more energy than `MAX_ENERGY_LEVEL`, or if the plan failed more than `max_replans` times.
+        ///
+        /// # Notes
+        ///
+        /// When the robot runs out of energy, the failed step is kept and retried at the next call,
+        /// or a new plan is computed once the robot recharged enough, if the current one needs more
+        /// energy than the robot has. While it waits, the goals are not planned again until the robot
+        /// recharged to the cost of the plan.
+        pub fn step(
+            &mut self,
+            robot: &mut impl Runnable,
+            world: &mut World,
+            index: &mut ResourceIndex,
+            tick: usize,
+        ) -> Result<ExecutionStatus, ToolError> {
+            index.update(&*world, tick)?;
+            if self.plan.is_none() {
+                if let Some(status) = self.replan(&*robot, index)? {
+                    return Ok(status);
+                }
+            }
+            for _ in 0..self.steps_per_tick {
+                let step = match self.pending.pop_front() {
+                    Some(step) => step,
+                    None => return Ok(ExecutionStatus::Done),
+                };
+                match self.perform(&step, robot, world) {
+                    Outcome::Done => {}
+                    Outcome::Failed(LibError::NotEnoughEnergy) => {
+                        self.last_failure = Some(LibError::NotEnoughEnergy);
+                        self.pending.push_front(step);
+                        return Ok(ExecutionStatus::Waiting);
+                    }
+                    outcome => {
+                        if let Outcome::Failed(e) = outcome {
+                            if let (Step::Go(direction), LibError::CannotWalk) = (&step, &e) {
+                                // the robot did not move: the tile it faces cannot be entered
+                                let position = MapCoordinate::from(robot.get_coordinate());
+                                let size = index.discovered().size();
+                                if let Some(target) =
+                                    position.checked_add(Offset::from(direction), size)
+                                {
+                                    self.blocked.insert(target);
+                                }
+                            }
+                            self.last_failure = Some(e);
+                        }
+                        return self.invalidate(world, index, tick);
+                    }
+                }
+            }
+            Ok(if self.pending.is_empty() {
+                ExecutionStatus::Done
+            } else {
+                ExecutionStatus::Running
+            })
+        }
+
+        /// Drops the current plan after a failure, so that the next call computes a new one.
+        fn invalidate(
+            &mut self,
+            world: &World,
+            index: &mut ResourceIndex,
+            tick: usize,
+        ) -> Result<ExecutionStatus, ToolError> {
+            if self.replans >= self.max_replans {
+                return Err(Other(format!("Plan failed after {} replans", self.replans)));
+            }
+            self.replans += 1;
+            self.plan = None;
+            self.pending.clear();
+            index.update(world, tick)?;
+            Ok(ExecutionStatus::Replanning)
+        }
+
+        /// Computes a new plan for the remaining goals.
+        ///
+        /// Returns the status to report instead of going on, if any.
+        fn replan(
+            &mut self,
+            robot: &impl Runnable,
+            index: &ResourceIndex,
+        ) -> Result<Option<ExecutionStatus>, ToolError> {
+            if self
+                .waiting_for
+                .is_some_and(|required| robot.get_energy().get_energy_level() < required)
+            {
+                return Ok(Some(ExecutionStatus::Waiting));
+            }
+            let mut constraints = Constraints::from_robot(robot)
+                .ending(self.end.clone())
+                .with_path_options(self.path_options);
+            for coordinate in &self.blocked {
+                constraints = constraints.avoiding(*coordinate);
+            }
+            let mapper = TileMapper {};
+            let goals = self.remaining_goals();
+            let plan = match mapper.solve_goals(index, robot, &goals, &constraints) {
+                Ok(plan) => plan,
+                Err(e) if lacks_energy(&e) => {
+                    // waiting only helps if a fully charged robot can carry out the plan
+                    let charged = Constraints {
+                        energy: MAX_ENERGY_LEVEL,
+                        ..constraints
+                    };
+                    let plan = mapper.solve_goals(index, robot, &goals, &charged)?;
+                    // the greedy plan does not depend on the energy: it fits once the robot has its cost
+                    self.waiting_for = Some(plan.cost);
+                    return Ok(Some(ExecutionStatus::Waiting));
+                }
+                Err(e) => return Err(e),
+            };
+            self.waiting_for = None;
+            self.pending = plan.actions.iter().flat_map(steps_of).collect();
+            self.plan = Some(plan);
+            Ok(None)
+        }
+
+        /// Calls robotics_lib for `step` and checks the result against the plan.
+        fn perform(
+            &mut self,
+            step: &Step,
+            robot: &mut impl Runnable,
+            world: &mut World,
+        ) -> Outcome {
+            match step {
+                Step::Go(direction) => match go(robot, world, direction.clone()) {
+                    Ok(_) => Outcome::Done,
+                    Err(e) => Outcome::Failed(e),
+                },
+                Step::Teleport(to) => {
+                    match teleport(robot, world, (to.get_height(), to.get_width())) {
+                        Ok(_) => Outcome::Done,
+                        Err(e) => Outcome::Failed(e),
+                    }
+                }
+                Step::Destroy { direction, amount } => {
+                    match destroy(robot, world, direction.clone()) {
+                        Ok(collected) if collected >= *amount => Outcome::Done,
+                        Ok(_) => Outcome::Stale,
+                        Err(e) => Outcome::Failed(e),
+                    }
+                }
+                Step::Put {
+                    direction,
+                    content,
+                    amount,
+                } => match put(robot, world, content.clone(), *amount, direction.clone()) {
+                    Ok(put) => {
+                        *self.delivered.entry(discriminant(content)).or_default() += put;
+                        if put >= *amount {
+                            Outcome::Done
+                        } else {
+                            Outcome::Stale
+                        }
+                    }
+                    Err(e) => Outcome::Failed(e),
+                },
+            }
+        }
+    }
+
+    /// Returns `true` if `error` is only due to the energy of the robot.
+    fn lacks_energy(error: &ToolError) -> bool {
+        match error {
+            InsufficientEnergy { .. } => true,
+            GoalUnmet { reason, .. } => matches!(**reason, InsufficientEnergy { .. }),
+            _ => false,
+        }
+    }
+
+    /// Splits an action into the calls to robotics_lib performing it.
+    fn steps_of(action: &Action) -> Vec<Step> {
+        match action {
+            Action::Move(path) => path
+                .steps
+                .iter()
+                .map(|step| match step {
+                    PathStep::Go(direction) => Step::Go(direction.clone()),
+                    PathStep::Teleport(to) => Step::Teleport(*to),
+                })
+                .collect(),
+            Action::Collect {
+                direction, amount, ..
+            } => vec![Step::Destroy {
+                direction: direction.clone(),
+                amount: *amount,
+            }],
+            Action::Deliver {
+                direction,
+                content,
+                amount,
+                ..
+            } => vec![Step::Put {
+                direction: direction.clone(),
+                content: content.clone(),
+                amount: *amount,
+            }],
+        }
+    }
+}
@@ -0,0 +1,30 @@
+pub mod test_maps {
+    use robotics_lib::world::tile::{Content, Tile, TileType};
+
+    /// Builds a fully discovered grass map of `size`x`size` tiles, with `contents`
+    /// placed at the given `(width, height)` coordinates.
+    ///
+    /// Shared by the unit and the integration tests, it is not part of the public API.
+    pub fn test_map(size: usize, contents: &[(usize, usize, Content)]) -> Vec<Vec<Option<Tile>>> {
+        let mut map: Vec<Vec<Option<Tile>>> = Vec::new();
+        for _ in 0..size {
+            let mut row: Vec<Option<Tile>> = Vec::new();
+            for _ in 0..size {
+                row.push(Some(Tile {
+                    tile_type: TileType::Grass,
+                    content: Content::None,
+                    elevation: 0,
+                }));
+            }
+            map.push(row);
+        }
+        for (width, height, content) in contents.iter() {
+            map[*height][*width] = Some(Tile {
+                tile_type: TileType::Grass,
+                content: content.clone(),
+                elevation: 0,
+            });
+        }
+        map
+    }
+}
@@ -0,0 +1,569 @@
+pub mod goal_solver {
+    use std::collections::{HashMap, HashSet};
+    use std::fmt::{Display, Formatter};
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::interface::Direction;
+    use robotics_lib::runner::Runnable;
+    use robotics_lib::world::tile::Content;
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::delivery::drop_off::{destination_for, free_capacity};
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{
+        ContentNotDiscovered, ContentUnreachable, GoalUnmet, InsufficientEnergy, Other,
+        Unreachable, WorldNotDiscovered,
+    };
+    use crate::path::path_finder::{Path, PathFinder, PathOptions};
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{content_name, stored_amount, ResourceMap, TileMapper};
+
+    /// The energy assumed to be spent by every `destroy` or `put`, used by `Constraints::new`.
+    pub const DEFAULT_INTERACTION_COST: usize = 3;
+
+    /// How many tiles of every goal, the closest ones as the crow flies, are priced with the
+    /// path finder at every step.
+    const CANDIDATES: usize = 8;
+
+    /// Something the robot must achieve.
+    ///
+    /// ## Variants
+    ///
+    /// - `Gather`: hold at least `amount` units of the same kind of `content` in the backpack at the end.
+    /// - `Deliver`: put `amount` units of the same kind of `content` where it is accepted, see `destination_for`.
+    ///   Units already in the backpack are delivered first; the missing ones are gathered.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Goal {
+        Gather { content: Content, amount: usize },
+        Deliver { content: Content, amount: usize },
+    }
+
+    impl Goal {
+        /// Gets the content of the goal.
+        pub fn content(&self) -> &Content {
+            match self {
+                Goal::Gather { content, .. } | Goal::Deliver { content, .. } => content,
+            }
+        }
+
+        /// Gets the amount of the goal.
+        pub fn amount(&self) -> usize {
+            match self {
+                Goal::Gather { amount, .. } | Goal::Deliver { amount, .. } => *amount,
+            }
+        }
+    }
+
+    impl Display for Goal {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Goal::Gather { content, amount } => {
+                    write!(f, "gather {} {}", amount, content_name(content))
+                }
+                Goal::Deliver { content, amount } => {
+                    write!(f, "deliver {} {}", amount, content_name(content))
+                }
+            }
+        }
+    }
+
+    /// Where the robot must be once every goal is met.
+    ///
+    /// ## Variants
+    ///
+    /// - `Anywhere`: the plan ends after the last goal.
+    /// - `At`: on the given tile.
+    /// - `Near`: next to a tile holding the same kind of the given content, e.g. a `Bank`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum EndLocation {
+        Anywhere,
+        At(MapCoordinate),
+        Near(Content),
+    }
+
+    /// The limits a plan must respect.
+    ///
+    /// ## Fields
+    ///
+    /// - `energy`: The energy available for the whole plan.
+    /// - `backpack_size`: How many units the backpack can hold.
+    /// - `backpack`: The units already in the backpack, by kind of content.
+    /// - `end`: Where the robot must be at the end. `Anywhere` by default.
+    /// - `path_options`: How paths are computed.
+    /// - `interaction_cost`: The energy assumed to be spent by every `destroy` or `put`.
+    /// - `blocked`: The tiles the robot must not enter, e.g. where a `go` failed. Empty by default.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Constraints {
+        pub energy: usize,
+        pub backpack_size: usize,
+        pub backpack: HashMap<Discriminant<Content>, usize>,
+        pub end: EndLocation,
+        pub path_options: PathOptions,
+        pub interaction_cost: usize,
+        pub blocked: HashSet<MapCoordinate>,
+    }
+
+    impl Constraints {
+        /// Creates the constraints of a robot with `energy` and an empty backpack of `backpack_size` units.
+        pub fn new(energy: usize, backpack_size: usize) -> Self {
+            Constraints {
+                energy,
+                backpack_size,
+                backpack: HashMap::new(),
+                end: EndLocation::Anywhere,
+                path_options: PathOptions::default(),
+                interaction_cost: DEFAULT_INTERACTION_COST,
+                blocked: HashSet::new(),
+            }
+        }
+
+        /// Creates the constraints of `robot`, from its energy level and its backpack.
+        pub fn from_robot(robot: &impl Runnable) -> Self {
+            let backpack = robot.get_backpack();
+            let mut constraints =
+                Constraints::new(robot.get_energy().get_energy_level(), backpack.get_size());
+            for (content, amount) in backpack.get_contents() {
+                constraints = constraints.carrying(content, *amount);
+            }
+            constraints
+        }
+
+        /// Adds `amount` units of `content` to the backpack.
+        pub fn carrying(mut self, content: &Content, amount: usize) -> Self {
+            if amount > 0 {
+                *self.backpack.entry(discriminant(content)).or_default() += amount;
+            }
+            self
+        }
+
+        /// Sets where the robot must be at the end.
+        pub fn ending(mut self, end: EndLocation) -> Self {
+            self.end = end;
+            self
+        }
+
+        /// Adds `coordinate` to the tiles the robot must not enter.
+        pub fn avoiding(mut self, coordinate: MapCoordinate) -> Self {
+            self.blocked.insert(coordinate);
+            self
+        }
+
+        /// Sets how paths are computed, e.g. with the weather multiplier of a `CostModel`.
+        pub fn with_path_options(mut self, path_options: PathOptions) -> Self {
+            self.path_options = path_options;
+            self
+        }
+    }
+
+    /// A single step of an `ActionPlan`.
+    ///
+    /// ## Variants
+    ///
+    /// - `Move`: follow the path, with `go` and `teleport`.
+    /// - `Collect`: `destroy` the content of the adjacent tile `target`, facing `direction`,
+    ///   expecting `amount` units.
+    /// - `Deliver`: `put` `amount` units of `content` into the adjacent tile `target`, facing `direction`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Action {
+        Move(Path),
+        Collect {
+            target: MapCoordinate,
+            direction: Direction,
+            content: Content,
+            amount: usize,
+        },
+        Deliver {
+            target: MapCoordinate,
+            direction: Direction,
+            content: Content,
+            amount: usize,
+        },
+    }
+
+    /// The plan returned by `solve_goals`.
+    ///
+    /// ## Fields
+    ///
+    /// - `actions`: The actions to perform, in order.
+    /// - `cost`: The estimated energy needed by the whole plan.
+    /// - `end`: Where the robot stands at the end.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ActionPlan {
+        pub actions: Vec<Action>,
+        pub cost: usize,
+        pub end: MapCoordinate,
+    }
+
+    impl ActionPlan {
+        /// Returns `true` if there is nothing to do.
+        pub fn is_empty(&self) -> bool {
+            self.actions.is_empty()
+        }
+
+        /// Returns the number of actions of the plan.
+        pub fn len(&self) -> usize {
+            self.actions.len()
+        }
+    }
+
+    /// The cheapest way found to make progress on a goal.
+    struct Candidate {
+        goal: usize,
+        path: Path,
+        direction: Direction,
+        target: MapCoordinate,
+        amount: usize,
+        // units held or accepted by the target before the action
+        available: usize,
+        deliver: bool,
+    }
+
+    /// What a goal needs next.
+    enum Progress {
+        Met,
+        Step(Candidate),
+        Blocked(ToolError),
+    }
+
+    /// The state of the robot while the plan is built.
+    struct Planner<'a> {
+        finder: PathFinder<'a>,
+        resources: &'a ResourceMap,
+        constraints: &'a Constraints,
+        goals: &'a [Goal],
+        position: MapCoordinate,
+        used: usize,
+        carried: HashMap<Discriminant<Content>, usize>,
+        delivered: HashMap<Discriminant<Content>, usize>,
+        // units put in every destination, and units left in every source already collected from
+        filled: HashMap<MapCoordinate, usize>,
+        remaining: HashMap<MapCoordinate, usize>,
+        actions: Vec<Action>,
+    }
+
+    impl<'a> Planner<'a> {
+        /// Sums the amounts of the goals of one kind, gathering or delivering.
+        fn target(&self, kind: &Discriminant<Content>, deliver: bool) -> usize {
+            self.goals
+                .iter()
+                .filter(|goal| discriminant(goal.content()) == *kind)
+                .filter(|goal| matches!(goal, Goal::Deliver { .. }) == deliver)
+                .map(Goal::amount)
+                .sum()
+        }
+
+        fn carried(&self, kind: &Discriminant<Content>) -> usize {
+            self.carried.get(kind).copied().unwrap_or(0)
+        }
+
+        fn progress(&self, index: usize) -> Progress {
+            let goal = &self.goals[index];
+            let kind = discriminant(goal.content());
+            let carried = self.carried(&kind);
+            let keep = self.target(&kind, false);
+            match goal {
+                Goal::Gather { .. } if carried >= keep => Progress::Met,
+                Goal::Gather { .. } => self.gather(index, &kind),
+                Goal::Deliver { content, .. } => {
+                    let delivered = self.delivered.get(&kind).copied().unwrap_or(0);
+                    let missing = self.target(&kind, true).saturating_sub(delivered);
+                    if missing == 0 {
+                        return Progress::Met;
+                    }
+                    let destination = match destination_for(content) {
+                        Some(destination) => destination,
+                        None => {
+                            return Progress::Blocked(Other(format!(
+                                "{} cannot be delivered anywhere",
+                                content_name(content)
+                            )))
+                        }
+                    };
+                    // units kept for the gathering goals are not delivered
+                    let deliverable = carried.saturating_sub(keep).min(missing);
+                    if deliverable == 0 {
+                        self.gather(index, &kind)
+                    } else {
+                        self.deliver(index, &discriminant(&destination), deliverable)
+                    }
+                }
+            }
+        }
+
+        fn gather(&self, index: usize, kind: &Discriminant<Content>) -> Progress {
+            let used: usize = self.carried.values().sum();
+            let free_space = self.constraints.backpack_size.saturating_sub(used);
+            if free_space == 0 {
+                return Progress::Blocked(Other("The backpack is full".to_string()));
+            }
+            let sources: Vec<(MapCoordinate, usize)> = self
+                .resources
+                .get(kind)
+                .into_iter()
+                .flatten()
+                .map(|(coordinate, quantity)| {
+                    let left = self.remaining.get(coordinate).copied();
+                    (*coordinate, left.unwrap_or_else(|| stored_amount(quantity)))
+                })
+                .filter(|(_, amount)| *amount > 0)
+                .collect();
+            match self.cheapest(*kind, sources) {
+                Ok((path, direction, target, available)) => Progress::Step(Candidate {
+                    goal: index,
+                    path,
+                    direction,
+                    target,
+                    amount: available.min(free_space),
+                    available,
+                    deliver: false,
+                }),
+                Err(e) => Progress::Blocked(e),
+            }
+        }
+
+        fn deliver(
+            &self,
+            index: usize,
+            destination: &Discriminant<Content>,
+            amount: usize,
+        ) -> Progress {
+            let destinations: Vec<(MapCoordinate, usize)> = self
+                .resources
+                .get(destination)
+                .into_iter()
+                .flatten()
+                .map(|(coordinate, quantity)| {
+                    let filled = self.filled.get(coordinate).copied().unwrap_or(0);
+                    (*coordinate, free_capacity(quantity).saturating_sub(filled))
+                })
+                .filter(|(_, capacity)| *capacity > 0)
+                .collect();
+            match self.cheapest(*destination, destinations) {
+                Ok((path, direction, target, capacity)) => Progress::Step(Candidate {
+                    goal: index,
+                    path,
+                    direction,
+                    target,
+                    amount: capacity.min(amount),
+                    available: capacity,
+                    deliver: true,
+                }),
+                Err(e) => Progress::Blocked(e),
+            }
+        }
+
+        /// Prices the tiles of `kind` closest to the robot, each with the amount it holds or accepts,
+        /// and returns the path to the cheapest one to reach, the direction to face at the end,
+        /// the tile and its amount.
+        fn cheapest(
+            &self,
+            kind: Discriminant<Content>,
+            mut tiles: Vec<(MapCoordinate, usize)>,
+        ) -> Result<(Path, Direction, MapCoordinate, usize), ToolError> {
+            if tiles.is_empty() {
+                return Err(ContentNotDiscovered(kind));
+            }
+            tiles.sort_by(|a, b| {
+                a.0.get_distance(&self.position)
+                    .total_cmp(&b.0.get_distance(&self.position))
+                    .then(a.0.cmp(&b.0))
+            });
+            tiles
+                .into_iter()
+                .take(CANDIDATES)
+                .filter_map(|(target, amount)| {
+                    let (path, direction) = self.finder.find_adjacent(self.position, target)?;
+                    Some((path, direction, target, amount))
+                })
+                .min_by_key(|(path, ..)| path.cost)
+                .ok_or(ContentUnreachable(kind))
+        }
+
+        fn apply(&mut self, candidate: Candidate) {
+            let content = self.goals[candidate.goal].content().clone();
+            let kind = discriminant(&content);
+            self.used += candidate.path.cost + self.constraints.interaction_cost;
+            self.position = candidate.path.end().unwrap_or(self.position);
+            if !candidate.path.is_empty() {
+                self.actions.push(Action::Move(candidate.path));
+            }
+            if candidate.deliver {
+                *self.carried.entry(kind).or_default() -= candidate.amount;
+                *self.delivered.entry(kind).or_default() += candidate.amount;
+                *self.filled.entry(candidate.target).or_default() += candidate.amount;
+                self.actions.push(Action::Deliver {
+                    target: candidate.target,
+                    direction: candidate.direction,
+                    content,
+                    amount: candidate.amount,
+                });
+            } else {
+                *self.carried.entry(kind).or_default() += candidate.amount;
+                // the units the backpack could not hold are left on the tile
+                self.remaining
+                    .insert(candidate.target, candidate.available - candidate.amount);
+                self.actions.push(Action::Collect {
+                    target: candidate.target,
+                    direction: candidate.direction,
+                    content,
+                    amount: candidate.amount,
+                });
+            }
+        }
+
+        /// Moves the robot to where the plan must end.
+        fn finish(&mut self) -> Result<(), ToolError> {
+            let path = match &self.constraints.end {
+                EndLocation::Anywhere => return Ok(()),
+                EndLocation::At(target) => self
+                    .finder
+                    .find(self.position, *target)
+                    .ok_or(Unreachable(*target))?,
+                EndLocation::Near(content) => {
+                    let kind = discriminant(content);
+                    let tiles = self
+                        .resources
+                        .get(&kind)
+                        .into_iter()
+                        .flatten()
+                        .map(|(coordinate, _)| (*coordinate, 0))
+                        .collect();
+                    self.cheapest(kind, tiles)?.0
+                }
+            };
+            if self.used + path.cost > self.constraints.energy {
+                return Err(InsufficientEnergy {
+                    required: self.used + path.cost,
+                    available: self.constraints.energy,
+                });
+            }
+            self.used += path.cost;
+            self.position = path.end().unwrap_or(self.position);
+            if !path.is_empty() {
+                self.actions.push(Action::Move(path));
+            }
+            Ok(())
+        }
+    }
+
+    impl TileMapper {
+        /// Plans how to meet a list of goals, e.g. "gather 20 wood, 10 rocks, deliver the garbage
+        /// and end near a bank".
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World or a `ResourceIndex`. It needs a tile grid.
+        /// * `origin` - Where the robot starts from.
+        /// * `goals` - The goals to meet.
+        /// * `constraints` - The energy, the backpack and where the robot must end.
+        ///
+        /// # Returns
+        ///
+        /// Returns the ordered actions meeting every goal, with their estimated energy cost.
+        /// Returns `GoalUnmet` with the reason if a goal cannot be met: its content was not discovered,
+        /// cannot be reached, cannot be delivered, or the energy or the backpack are not enough.
+        ///
+        /// # Notes
+        ///
+        /// The plan is built greedily: at every step the cheapest action making progress on any goal is
+        /// chosen, among the closest tiles of every goal. A `destroy` is assumed to collect the whole
+        /// content of a tile, as much as the backpack can hold: the rest is left on the tile and can be
+        /// collected later, e.g. after a delivery freed the backpack.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::goals::goal_solver::{Constraints, EndLocation, Goal};
+        ///
+        /// let goals = [
+        ///     Goal::Gather { content: Content::Tree(0), amount: 20 },
+        ///     Goal::Gather { content: Content::Rock(0), amount: 10 },
+        ///     Goal::Deliver { content: Content::Garbage(0), amount: 5 },
+        /// ];
+        /// let constraints = Constraints::from_robot(self).ending(EndLocation::Near(Content::Bank(0..0)));
+        ///
+        /// match mapper.solve_goals(&index, self, &goals, &constraints) {
+        ///     Ok(plan) => println!("{} actions, {} energy", plan.len(), plan.cost),
+        ///     Err(e) => println!("{}", e)
+        /// }
+        /// ```
+        pub fn solve_goals<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            goals: &[Goal],
+            constraints: &Constraints,
+        ) -> Result<ActionPlan, ToolError> {
+            let (tiles, resources) = match (source.tiles(), source.resources()) {
+                (Some(tiles), Some(resources)) => (tiles, resources),
+                _ => return Err(WorldNotDiscovered),
+            };
+            let mut planner = Planner {
+                finder: PathFinder::for_source(&tiles, source, constraints.path_options)
+                    .with_blocked(&constraints.blocked),
+                resources: &resources,
+                constraints,
+                goals,
+                position: origin.location(),
+                used: 0,
+                carried: constraints.backpack.clone(),
+                delivered: HashMap::new(),
+                filled: HashMap::new(),
+                remaining: HashMap::new(),
+                actions: vec![],
+            };
+
+            loop {
+                let mut best: Option<Candidate> = None;
+                let mut blocked: Option<(usize, ToolError)> = None;
+                for index in 0..goals.len() {
+                    match planner.progress(index) {
+                        Progress::Met => {}
+                        Progress::Step(candidate) => {
+                            if best
+                                .as_ref()
+                                .is_none_or(|best| candidate.path.cost < best.path.cost)
+                            {
+                                best = Some(candidate);
+                            }
+                        }
+                        Progress::Blocked(e) => {
+                            blocked.get_or_insert((index, e));
+                        }
+                    }
+                }
+                match (best, blocked) {
+                    (Some(candidate), _) => {
+                        let required =
+                            planner.used + candidate.path.cost + constraints.interaction_cost;
+                        if required > constraints.energy {
+                            return Err(GoalUnmet {
+                                goal: goals[candidate.goal].to_string(),
+                                reason: Box::new(InsufficientEnergy {
+                                    required,
+                                    available: constraints.energy,
+                                }),
+                            });
+                        }
+                        planner.apply(candidate);
+                    }
+                    // goals blocked while others progress may be unblocked later, e.g. by
+                    // delivering to free the backpack
+                    (None, Some((index, reason))) => {
+                        return Err(GoalUnmet {
+                            goal: goals[index].to_string(),
+                            reason: Box::new(reason),
+                        })
+                    }
+                    (None, None) => break,
+                }
+            }
+
+            planner.finish()?;
+            Ok(ActionPlan {
+                actions: planner.actions,
+                cost: planner.used,
+                end: planner.position,
+            })
+        }
+    }
+}
@@ -0,0 +1,282 @@
+pub mod resource_history {
+    use std::collections::HashMap;
+    use std::fmt::{Display, Formatter};
+    use std::fs::{File, OpenOptions};
+    use std::io::{BufRead, BufReader, Write};
+    use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+
+    use crate::coordinates::map_coordinate::MapCoordinate;
+    use crate::diff::resource_diff::{DiffEntry, ResourceDiff};
+    use crate::errors::tool_errors::ToolError;
+    use crate::tool::tile_mapper::{
+        format_quantity, kind_from_name, kind_name, parse_quantity, ResourceMap,
+    };
+
+    /// A change of the resource index, together with the tick it happened at.
+    ///
+    /// Records are stored one per line, as `<tick> <op> <content> <width>,<height> <quantity>`,
+    /// where `op` is `+`, `-` or `~`; changed quantities list both the old and the new value.
+    ///
+    /// ## Example
+    ///
+    /// ```text
+    /// 100 + Rock 3,1 17
+    /// 200 ~ Rock 3,1 17 10
+    /// 200 - Coin 2,1 3
+    /// ```
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct HistoryRecord {
+        pub tick: usize,
+        pub entry: DiffEntry,
+    }
+
+    impl Display for HistoryRecord {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            let coordinate = self.entry.coordinate();
+            let position = format!("{},{}", coordinate.get_width(), coordinate.get_height());
+            let name = kind_name(&self.entry.kind());
+            match &self.entry {
+                DiffEntry::Added { quantity, .. } => {
+                    write!(
+                        f,
+                        "{} + {} {} {}",
+                        self.tick,
+                        name,
+                        position,
+                        format_quantity(quantity)
+                    )
+                }
+                DiffEntry::Removed { quantity, .. } => {
+                    write!(
+                        f,
+                        "{} - {} {} {}",
+                        self.tick,
+                        name,
+                        position,
+                        format_quantity(quantity)
+                    )
+                }
+                DiffEntry::Changed { before, after, .. } => write!(
+                    f,
+                    "{} ~ {} {} {} {}",
+                    self.tick,
+                    name,
+                    position,
+                    format_quantity(before),
+                    format_quantity(after)
+                ),
+            }
+        }
+    }
+
+    impl FromStr for HistoryRecord {
+        type Err = ToolError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let error = || ToolError::Other(format!("Invalid history record: {}", s));
+            let fields: Vec<&str> = s.split_whitespace().collect();
+            if fields.len() < 5 {
+                return Err(error());
+            }
+            let tick = fields[0].parse().map_err(|_| error())?;
+            let kind = kind_from_name(fields[2]).ok_or_else(error)?;
+            let coordinate: MapCoordinate = fields[3].parse()?;
+            let quantity = parse_quantity(fields[4]).ok_or_else(error)?;
+
+            let entry = match (fields[1], fields.get(5)) {
+                ("+", None) => DiffEntry::Added {
+                    kind,
+                    coordinate,
+                    quantity,
+                },
+                ("-", None) => DiffEntry::Removed {
+                    kind,
+                    coordinate,
+                    quantity,
+                },
+                ("~", Some(after)) => DiffEntry::Changed {
+                    kind,
+                    coordinate,
+                    before: quantity,
+                    after: parse_quantity(after).ok_or_else(error)?,
+                },
+                _ => return Err(error()),
+            };
+            Ok(HistoryRecord { tick, entry })
+        }
+    }
+
+    /// The `HistoryRecorder` appends every change of a `ResourceIndex` to a file.
+    ///
+    /// The file is opened in append mode, so a recording can be resumed across runs.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::history::resource_history::HistoryRecorder;
+    /// use tile_resource_mapper_tool::index::resource_index::ResourceIndex;
+    ///
+    /// let recorder = HistoryRecorder::create("exploration.history")?;
+    /// let mut index = ResourceIndex::with_recorder(recorder);
+    /// ```
+    pub struct HistoryRecorder {
+        file: File,
+        path: PathBuf,
+    }
+
+    impl HistoryRecorder {
+        /// Opens `path` for appending, creating the file if it does not exist.
+        pub fn create(path: impl AsRef<Path>) -> Result<Self, ToolError> {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path.as_ref())?;
+            Ok(HistoryRecorder {
+                file,
+                path: path.as_ref().to_path_buf(),
+            })
+        }
+
+        /// Gets the path of the file the recorder writes to.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Appends every entry of `diff` to the file, marked with `tick`.
+        pub fn record(&mut self, tick: usize, diff: &ResourceDiff) -> Result<(), ToolError> {
+            for entry in diff.entries() {
+                let record = HistoryRecord {
+                    tick,
+                    entry: entry.clone(),
+                };
+                writeln!(self.file, "{}", record)?;
+            }
+            self.file.flush()?;
+            Ok(())
+        }
+    }
+
+    /// The `HistoryReplayer` rebuilds the state of a resource index from a recorded history.
+    ///
+    /// It can either jump to the state at any tick with `state_at`, or step through the
+    /// recorded ticks one at a time with `step`.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::history::resource_history::HistoryReplayer;
+    ///
+    /// let mut replayer = HistoryReplayer::open("exploration.history")?;
+    ///
+    /// let map_at_150 = replayer.state_at(150);
+    ///
+    /// while let Some((tick, changes)) = replayer.step() {
+    ///     println!("tick {}:\n{}", tick, changes.summary());
+    /// }
+    /// ```
+    #[derive(Debug, Clone, Default)]
+    pub struct HistoryReplayer {
+        records: Vec<HistoryRecord>,
+        position: usize,
+        state: ResourceMap,
+        tick: Option<usize>,
+    }
+
+    impl HistoryReplayer {
+        /// Reads a history written by a `HistoryRecorder`.
+        ///
+        /// Returns an error if the file cannot be read or contains an invalid line.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, ToolError> {
+            let reader = BufReader::new(File::open(path.as_ref())?);
+            let mut records = vec![];
+            for (number, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record = line
+                    .parse::<HistoryRecord>()
+                    .map_err(|e| ToolError::CorruptSave {
+                        path: path.as_ref().to_path_buf(),
+                        line: Some(number + 1),
+                        source: Box::new(e),
+                    })?;
+                records.push(record);
+            }
+            Ok(HistoryReplayer::from_records(records))
+        }
+
+        /// Creates a replayer over already parsed records.
+        pub fn from_records(mut records: Vec<HistoryRecord>) -> Self {
+            // the sort is stable, so records of the same tick keep their order
+            records.sort_by_key(|record| record.tick);
+            HistoryReplayer {
+                records,
+                position: 0,
+                state: HashMap::new(),
+                tick: None,
+            }
+        }
+
+        /// Gets every recorded record.
+        pub fn records(&self) -> &[HistoryRecord] {
+            &self.records
+        }
+
+        /// Gets the ticks at which something changed, in increasing order.
+        pub fn ticks(&self) -> Vec<usize> {
+            let mut ticks: Vec<usize> = self.records.iter().map(|r| r.tick).collect();
+            ticks.dedup();
+            ticks
+        }
+
+        /// Returns the state of the index at `tick`, i.e. after applying every record up to `tick` included.
+        pub fn state_at(&self, tick: usize) -> ResourceMap {
+            let entries = self
+                .records
+                .iter()
+                .take_while(|record| record.tick <= tick)
+                .map(|record| record.entry.clone())
+                .collect();
+            let mut state = HashMap::new();
+            ResourceDiff::from_entries(entries).apply_to(&mut state);
+            state
+        }
+
+        /// Applies the records of the next recorded tick.
+        ///
+        /// Returns the tick and its changes, or `None` once the end of the history is reached.
+        pub fn step(&mut self) -> Option<(usize, ResourceDiff)> {
+            let tick = self.records.get(self.position)?.tick;
+            let entries: Vec<DiffEntry> = self.records[self.position..]
+                .iter()
+                .take_while(|record| record.tick == tick)
+                .map(|record| record.entry.clone())
+                .collect();
+            self.position += entries.len();
+
+            let diff = ResourceDiff::from_entries(entries);
+            diff.apply_to(&mut self.state);
+            self.tick = Some(tick);
+            Some((tick, diff))
+        }
+
+        /// Goes back to the beginning of the history.
+        pub fn rewind(&mut self) {
+            self.position = 0;
+            self.state = HashMap::new();
+            self.tick = None;
+        }
+
+        /// Gets the state reached by the last `step`.
+        pub fn state(&self) -> &ResourceMap {
+            &self.state
+        }
+
+        /// Gets the tick reached by the last `step`, `None` if no step has been made yet.
+        pub fn tick(&self) -> Option<usize> {
+            self.tick
+        }
+    }
+}
@@ -0,0 +1,206 @@
+pub mod resource_index {
+    use std::collections::HashMap;
+    use std::sync::mpsc::Receiver;
+
+    use robotics_lib::world::tile::Tile;
+
+    use crate::coordinates::map_coordinate::MapCoordinate;
+    use crate::diff::resource_diff::{DiffEntry, ResourceDiff};
+    use crate::discovery::discovery_mask::DiscoveryMask;
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::WorldNotDiscovered;
+    use crate::history::resource_history::HistoryRecorder;
+    use crate::observer::resource_observer::{Observers, ResourceFilter, SubscriptionId};
+    use crate::path::path_finder::find_teleports;
+    use crate::source::map_source::MapSource;
+    use crate::spatial::spatial_index::SpatialIndex;
+    use crate::tool::tile_mapper::{collect_resources, ResourceMap};
+
+    /// The `ResourceIndex` struct keeps the resources discovered by the robot between two ticks,
+    /// so that every update only reports what changed since the previous one.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::index::resource_index::ResourceIndex;
+    ///
+    /// let mut index = ResourceIndex::new();
+    ///
+    /// // inside process_tick
+    /// let changes = index.update(world, tick)?;
+    /// println!("{}", changes.summary());
+    /// ```
+    #[derive(Default)]
+    pub struct ResourceIndex {
+        resources: ResourceMap,
+        spatial: SpatialIndex,
+        tiles: Option<Vec<Vec<Option<Tile>>>>,
+        discovered: DiscoveryMask,
+        teleports: Vec<MapCoordinate>,
+        tick: usize,
+        recorder: Option<HistoryRecorder>,
+        observers: Observers,
+    }
+
+    impl ResourceIndex {
+        /// Creates an empty `ResourceIndex`.
+        pub fn new() -> Self {
+            ResourceIndex {
+                resources: HashMap::new(),
+                spatial: SpatialIndex::new(),
+                tiles: None,
+                discovered: DiscoveryMask::default(),
+                teleports: vec![],
+                tick: 0,
+                recorder: None,
+                observers: Observers::new(),
+            }
+        }
+
+        /// Creates an empty `ResourceIndex` that logs every change to `recorder`.
+        pub fn with_recorder(recorder: HistoryRecorder) -> Self {
+            ResourceIndex {
+                recorder: Some(recorder),
+                ..ResourceIndex::new()
+            }
+        }
+
+        /// Creates a `ResourceIndex` already containing `resources`, e.g. a map rebuilt by a replayer.
+        pub fn from_map(resources: ResourceMap, tick: usize) -> Self {
+            ResourceIndex {
+                spatial: SpatialIndex::from_resources(&resources),
+                resources,
+                tiles: None,
+                discovered: DiscoveryMask::default(),
+                teleports: vec![],
+                tick,
+                recorder: None,
+                observers: Observers::new(),
+            }
+        }
+
+        /// Gets the resources currently stored in the index.
+        pub fn resources(&self) -> &ResourceMap {
+            &self.resources
+        }
+
+        /// Gets the spatial index of the stored resources, for fast nearest-neighbour and range queries.
+        pub fn spatial(&self) -> &SpatialIndex {
+            &self.spatial
+        }
+
+        /// Gets the grid of tiles read by the last update, if it had one.
+        pub fn tiles(&self) -> Option<&[Vec<Option<Tile>>]> {
+            self.tiles.as_deref()
+        }
+
+        /// Gets the mask of the tiles discovered so far.
+        ///
+        /// Only updates reading a grid of tiles mark tiles as discovered.
+        pub fn discovered(&self) -> &DiscoveryMask {
+            &self.discovered
+        }
+
+        /// Gets the activated teleports discovered so far, in reading order.
+        pub fn teleports(&self) -> &[MapCoordinate] {
+            &self.teleports
+        }
+
+        /// Gets the tick of the last update.
+        pub fn tick(&self) -> usize {
+            self.tick
+        }
+
+        /// Calls `callback` with every change passing `filter`, as soon as an update finds it.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::observer::resource_observer::ResourceFilter;
+        ///
+        /// index.subscribe(ResourceFilter::new().content(&Content::Market(0)), |entry| {
+        ///     println!("{}", entry);
+        /// });
+        /// ```
+        pub fn subscribe(
+            &mut self,
+            filter: ResourceFilter,
+            callback: impl FnMut(&DiffEntry) + Send + 'static,
+        ) -> SubscriptionId {
+            self.observers.subscribe(filter, callback)
+        }
+
+        /// Sends every change passing `filter` to the returned receiver, as soon as an update finds it.
+        ///
+        /// The subscription is cancelled once the receiver is dropped.
+        pub fn subscribe_channel(
+            &mut self,
+            filter: ResourceFilter,
+        ) -> (SubscriptionId, Receiver<DiffEntry>) {
+            self.observers.subscribe_channel(filter)
+        }
+
+        /// Cancels a subscription, returning `false` if it did not exist.
+        pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+            self.observers.unsubscribe(id)
+        }
+
+        /// Scans the tiles discovered in `source` and updates the index.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the `World`.
+        /// * `tick` - The current tick, stored together with the changes.
+        ///
+        /// # Returns
+        ///
+        /// Returns the changes since the previous update, after notifying the subscribers.
+        /// Returns an error if the world has not been discovered yet or the recorder fails to write.
+        pub fn update<S: MapSource + ?Sized>(
+            &mut self,
+            source: &S,
+            tick: usize,
+        ) -> Result<ResourceDiff, ToolError> {
+            if let Some(robot_world) = source.tiles() {
+                return self.update_from_map(robot_world.into_owned(), tick);
+            }
+            match source.resources() {
+                Some(resources) => {
+                    self.tiles = None;
+                    self.teleports = vec![];
+                    self.replace(resources.into_owned(), tick)
+                }
+                None => Err(WorldNotDiscovered),
+            }
+        }
+
+        /// Same as `update`, but reads the tiles from a map such as the one returned by `robot_map`.
+        pub fn update_from_map(
+            &mut self,
+            robot_world: Vec<Vec<Option<Tile>>>,
+            tick: usize,
+        ) -> Result<ResourceDiff, ToolError> {
+            self.teleports = find_teleports(&robot_world);
+            let resources = collect_resources(&robot_world);
+            self.discovered.update(&robot_world);
+            self.tiles = Some(robot_world);
+            self.replace(resources, tick)
+        }
+
+        /// Replaces the stored resources and logs the differences to the recorder, if any.
+        fn replace(
+            &mut self,
+            resources: ResourceMap,
+            tick: usize,
+        ) -> Result<ResourceDiff, ToolError> {
+            let diff = ResourceDiff::between(&self.resources, &resources);
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.record(tick, &diff)?;
+            }
+            self.spatial = SpatialIndex::from_resources(&resources);
+            self.resources = resources;
+            self.tick = tick;
+            self.observers.notify(&diff);
+            Ok(diff)
+        }
+    }
+}
@@ -0,0 +1,158 @@
+pub mod resource_iterator {
+    use std::collections::HashMap;
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::world::tile::{Content, Tile};
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::region::map_region::Rect;
+    use crate::tool::tile_mapper::{ResourceMap, TileMapper};
+
+    /// The iterator returned by `TileMapper::iter_resources`.
+    ///
+    /// It walks the discovered tiles holding some content in reading order, borrowing the grid,
+    /// so tiles are only visited while the iterator is consumed.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+    ///
+    /// let first_coins: Vec<MapCoordinate> = TileMapper::iter_resources(index.tiles().unwrap_or_default())
+    ///     .of_kind(&Content::Coin(0))
+    ///     .take(3)
+    ///     .map(|(coordinate, _, _)| coordinate)
+    ///     .collect();
+    /// ```
+    #[derive(Clone)]
+    pub struct Resources<'a> {
+        robot_world: &'a [Vec<Option<Tile>>],
+        row: usize,
+        column: usize,
+        kind: Option<Discriminant<Content>>,
+        region: Option<Rect>,
+    }
+
+    impl<'a> Resources<'a> {
+        /// Keeps only the tiles holding the same kind of `content`.
+        pub fn of_kind(mut self, content: &Content) -> Self {
+            let kind = discriminant(content);
+            if self.kind.is_some_and(|old| old != kind) {
+                // no tile holds two kinds of content
+                self.row = self.robot_world.len();
+            }
+            self.kind = Some(kind);
+            self
+        }
+
+        /// Keeps only the tiles inside `region`.
+        pub fn within(mut self, region: Rect) -> Self {
+            self.region = match self.region {
+                Some(old) => match old.intersection(&region) {
+                    Some(both) => Some(both),
+                    None => {
+                        self.row = self.robot_world.len();
+                        Some(region)
+                    }
+                },
+                None => Some(region),
+            };
+            self
+        }
+
+        /// Consumes the iterator, returning the tile closest to `origin`.
+        ///
+        /// In case of two tiles at the same distance, the first one in reading order is returned,
+        /// as in `find_closest`.
+        pub fn closest_to<K>(self, origin: &impl Locatable<K>) -> Option<MapCoordinate> {
+            let origin = origin.location();
+            self.map(|(coordinate, _, _)| (coordinate, coordinate.get_distance(&origin)))
+                .fold(None, |closest, (coordinate, distance)| match closest {
+                    Some((_, old_distance)) if old_distance <= distance => closest,
+                    _ => Some((coordinate, distance)),
+                })
+                .map(|(coordinate, _)| coordinate)
+        }
+
+        /// Consumes the iterator, grouping the remaining tiles in a `ResourceMap`, so that they
+        /// can be passed to any query of `TileMapper`.
+        pub fn into_resource_map(self) -> ResourceMap {
+            let mut resources: ResourceMap = HashMap::new();
+            for (coordinate, content, _) in self {
+                resources
+                    .entry(discriminant(content))
+                    .or_default()
+                    .push((coordinate, content.get_value()));
+            }
+            resources
+        }
+
+        /// Checks whether a tile passes the filters of the iterator.
+        fn accepts(&self, coordinate: &MapCoordinate, tile: &Tile) -> bool {
+            tile.content != Content::None
+                && self
+                    .kind
+                    .is_none_or(|kind| kind == discriminant(&tile.content))
+                && self.region.is_none_or(|region| region.contains(coordinate))
+        }
+    }
+
+    impl<'a> Iterator for Resources<'a> {
+        type Item = (MapCoordinate, &'a Content, &'a Tile);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let robot_world = self.robot_world;
+            while let Some(row_vector) = robot_world.get(self.row) {
+                while let Some(element) = row_vector.get(self.column) {
+                    let coordinate = MapCoordinate::new(self.column, self.row);
+                    self.column += 1;
+                    if let Some(tile) = element {
+                        if self.accepts(&coordinate, tile) {
+                            return Some((coordinate, &tile.content, tile));
+                        }
+                    }
+                }
+                self.row += 1;
+                self.column = 0;
+            }
+            None
+        }
+    }
+
+    impl TileMapper {
+        /// Iterates lazily over the discovered tiles holding some content, in reading order.
+        ///
+        /// # Arguments
+        ///
+        /// * `robot_world` - The grid to walk, e.g. the one returned by `robot_map` or `ResourceIndex::tiles`.
+        ///
+        /// # Returns
+        ///
+        /// Returns a `Resources` iterator of `(MapCoordinate, &Content, &Tile)`, borrowing `robot_world`.
+        ///
+        /// # Notes
+        ///
+        /// Unlike `collection`, no `HashMap` is built: stopping early, e.g. with `take`, skips the
+        /// rest of the grid. Use `of_kind` and `within` to filter the tiles, and `closest_to` or
+        /// `into_resource_map` to go on with the other queries.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let robot_world = robot_map(world).unwrap();
+        /// for (coordinate, content, tile) in TileMapper::iter_resources(&robot_world) {
+        ///     println!("{} {:?} on {:?}", coordinate, content, tile.tile_type);
+        /// }
+        /// ```
+        pub fn iter_resources(robot_world: &[Vec<Option<Tile>>]) -> Resources<'_> {
+            Resources {
+                robot_world,
+                row: 0,
+                column: 0,
+                kind: None,
+                region: None,
+            }
+        }
+    }
+}
@@ -1,5 +1,26 @@
+pub mod clustering;
 pub mod coordinates;
-mod errors;
+pub mod cost;
+pub mod delivery;
+pub mod depletion;
+pub mod diff;
+pub mod discovery;
+pub mod errors;
+pub mod executor;
+#[doc(hidden)]
+pub mod fixtures;
+pub mod goals;
+pub mod history;
+pub mod index;
+pub mod iteration;
+pub mod observer;
+pub mod path;
+pub mod prediction;
+pub mod region;
+pub mod scoring;
+pub mod source;
+pub mod spatial;
+pub mod statistics;
 #[cfg(test)]
 mod tests;
 pub mod tool;
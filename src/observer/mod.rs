@@ -0,0 +1,185 @@
+pub mod resource_observer {
+    use std::mem::{discriminant, Discriminant};
+    use std::sync::mpsc::{channel, Receiver, Sender};
+
+    use robotics_lib::world::tile::Content;
+
+    use crate::diff::resource_diff::{DiffEntry, ResourceDiff};
+    use crate::region::map_region::Rect;
+    use crate::tool::tile_mapper::stored_amount;
+
+    /// Which changes of the resource index a subscriber is notified of.
+    ///
+    /// An empty filter lets every change through; every condition added narrows it down.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::observer::resource_observer::ResourceFilter;
+    ///
+    /// let filter = ResourceFilter::new()
+    ///     .content(&Content::Market(0))
+    ///     .content(&Content::JollyBlock(0))
+    ///     .within(Rect::around(robot_position, 10, size).unwrap());
+    /// ```
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct ResourceFilter {
+        kinds: Vec<Discriminant<Content>>,
+        min_quantity: Option<usize>,
+        region: Option<Rect>,
+    }
+
+    impl ResourceFilter {
+        /// Creates a filter letting every change through.
+        pub fn new() -> Self {
+            ResourceFilter::default()
+        }
+
+        /// Lets through the changes of tiles holding the same kind of `content`.
+        ///
+        /// Can be called several times to accept several kinds.
+        pub fn content(mut self, content: &Content) -> Self {
+            let kind = discriminant(content);
+            if !self.kinds.contains(&kind) {
+                self.kinds.push(kind);
+            }
+            self
+        }
+
+        /// Lets through only the tiles holding at least `amount` units, as in `ResourceEntry::amount`.
+        ///
+        /// Removed tiles are compared by the amount they held before, changed tiles by the new one.
+        pub fn min_quantity(mut self, amount: usize) -> Self {
+            self.min_quantity = Some(amount);
+            self
+        }
+
+        /// Lets through only the tiles inside `region`.
+        pub fn within(mut self, region: Rect) -> Self {
+            self.region = Some(region);
+            self
+        }
+
+        /// Checks whether `entry` passes the filter.
+        pub fn matches(&self, entry: &DiffEntry) -> bool {
+            let quantity = match entry {
+                DiffEntry::Added { quantity, .. } | DiffEntry::Removed { quantity, .. } => quantity,
+                DiffEntry::Changed { after, .. } => after,
+            };
+            (self.kinds.is_empty() || self.kinds.contains(&entry.kind()))
+                && self
+                    .min_quantity
+                    .is_none_or(|amount| stored_amount(quantity) >= amount)
+                && self
+                    .region
+                    .is_none_or(|region| region.contains(&entry.coordinate()))
+        }
+    }
+
+    /// Identifies a subscription, to cancel it with `unsubscribe`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct SubscriptionId(usize);
+
+    /// Where the changes of a subscription are delivered.
+    enum Subscriber {
+        Callback(Box<dyn FnMut(&DiffEntry) + Send>),
+        Channel(Sender<DiffEntry>),
+    }
+
+    struct Subscription {
+        id: SubscriptionId,
+        filter: ResourceFilter,
+        subscriber: Subscriber,
+    }
+
+    /// The `Observers` struct keeps the subscriptions of a `ResourceIndex` and notifies them
+    /// of the changes found by every update.
+    ///
+    /// Subscribers are notified in the order they subscribed, and every subscriber receives
+    /// the entries in the order of the `ResourceDiff`.
+    #[derive(Default)]
+    pub struct Observers {
+        subscriptions: Vec<Subscription>,
+        next_id: usize,
+    }
+
+    impl Observers {
+        /// Creates an empty set of observers.
+        pub fn new() -> Self {
+            Observers::default()
+        }
+
+        /// Calls `callback` with every change passing `filter`.
+        ///
+        /// The callback must be `Send`, so that the index keeping it can still move across threads.
+        pub fn subscribe(
+            &mut self,
+            filter: ResourceFilter,
+            callback: impl FnMut(&DiffEntry) + Send + 'static,
+        ) -> SubscriptionId {
+            self.push(filter, Subscriber::Callback(Box::new(callback)))
+        }
+
+        /// Sends every change passing `filter` to the returned receiver.
+        ///
+        /// The subscription is cancelled by itself once the receiver is dropped.
+        pub fn subscribe_channel(
+            &mut self,
+            filter: ResourceFilter,
+        ) -> (SubscriptionId, Receiver<DiffEntry>) {
+            let (sender, receiver) = channel();
+            (self.push(filter, Subscriber::Channel(sender)), receiver)
+        }
+
+        /// Cancels a subscription, returning `false` if it did not exist.
+        pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+            let before = self.subscriptions.len();
+            self.subscriptions
+                .retain(|subscription| subscription.id != id);
+            self.subscriptions.len() != before
+        }
+
+        /// Gets the number of active subscriptions.
+        pub fn len(&self) -> usize {
+            self.subscriptions.len()
+        }
+
+        /// Checks whether there is no active subscription.
+        pub fn is_empty(&self) -> bool {
+            self.subscriptions.is_empty()
+        }
+
+        /// Notifies every subscriber of the entries of `diff` passing its filter.
+        pub fn notify(&mut self, diff: &ResourceDiff) {
+            self.subscriptions.retain_mut(|subscription| {
+                for entry in diff
+                    .entries()
+                    .iter()
+                    .filter(|entry| subscription.filter.matches(entry))
+                {
+                    match &mut subscription.subscriber {
+                        Subscriber::Callback(callback) => callback(entry),
+                        Subscriber::Channel(sender) => {
+                            // the receiver was dropped: nobody is listening anymore
+                            if sender.send(entry.clone()).is_err() {
+                                return false;
+                            }
+                        }
+                    }
+                }
+                true
+            });
+        }
+
+        fn push(&mut self, filter: ResourceFilter, subscriber: Subscriber) -> SubscriptionId {
+            let id = SubscriptionId(self.next_id);
+            self.next_id += 1;
+            self.subscriptions.push(Subscription {
+                id,
+                filter,
+                subscriber,
+            });
+            id
+        }
+    }
+}
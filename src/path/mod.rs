@@ -0,0 +1,571 @@
+pub mod path_finder {
+    use std::cmp::Reverse;
+    use std::collections::{BinaryHeap, HashMap, HashSet};
+    use std::mem::discriminant;
+
+    use robotics_lib::interface::Direction;
+    use robotics_lib::world::tile::{Content, Tile, TileType};
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate, Offset};
+    use crate::discovery::discovery_mask::DiscoveryMask;
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{
+        ContentNotDiscovered, ContentUnreachable, OutOfBounds, Unreachable, WorldNotDiscovered,
+    };
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{collect_resources, TileMapper};
+
+    /// The energy a teleport hop is assumed to cost when no other value is given in `PathOptions`.
+    pub const DEFAULT_TELEPORT_COST: usize = 30;
+
+    /// What `PathFinder::tile` returns for a tile the discovery mask does not know.
+    static UNDISCOVERED: Option<Tile> = None;
+
+    /// How the path finder handles the tiles the robot has not discovered yet.
+    ///
+    /// ## Variants
+    ///
+    /// - `Blocked`: undiscovered tiles are never crossed.
+    /// - `Cost`: undiscovered tiles are assumed walkable and flat, and entering one costs the given amount of energy.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum UnknownTiles {
+        Blocked,
+        Cost(usize),
+    }
+
+    /// The options of a `PathFinder`.
+    ///
+    /// ## Fields
+    ///
+    /// - `unknown_tiles`: How undiscovered tiles are handled, `Blocked` by default.
+    /// - `elevation`: Whether climbing costs the square of the elevation difference, as in `go`. `true` by default.
+    /// - `teleport_cost`: The energy spent to jump between two activated teleports, `None` to never use them.
+    ///   `Some(DEFAULT_TELEPORT_COST)` by default.
+    /// - `weather_multiplier`: The factor applied to the cost of every move, e.g. from a `CostModel`. `1.0` by default.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct PathOptions {
+        pub unknown_tiles: UnknownTiles,
+        pub elevation: bool,
+        pub teleport_cost: Option<usize>,
+        pub weather_multiplier: f64,
+    }
+
+    impl Default for PathOptions {
+        fn default() -> Self {
+            PathOptions {
+                unknown_tiles: UnknownTiles::Blocked,
+                elevation: true,
+                teleport_cost: Some(DEFAULT_TELEPORT_COST),
+                weather_multiplier: 1.,
+            }
+        }
+    }
+
+    /// A single step of a `Path`.
+    ///
+    /// ## Variants
+    ///
+    /// - `Go`: a move to an adjacent tile, to perform with `go`.
+    /// - `Teleport`: a jump from the current teleport to the teleport at the given coordinates, to perform with `teleport`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum PathStep {
+        Go(Direction),
+        Teleport(MapCoordinate),
+    }
+
+    /// A path between two tiles.
+    ///
+    /// ## Fields
+    ///
+    /// - `steps`: The moves and teleport hops to perform, in order.
+    /// - `tiles`: The tiles the robot stands on, from the start to the end included.
+    /// - `cost`: The estimated energy needed to walk the path.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Path {
+        pub steps: Vec<PathStep>,
+        pub tiles: Vec<MapCoordinate>,
+        pub cost: usize,
+    }
+
+    impl Path {
+        /// Returns the directions to give to `go`, one per step,
+        /// or `None` if the path uses a teleport.
+        pub fn directions(&self) -> Option<Vec<Direction>> {
+            self.steps
+                .iter()
+                .map(|step| match step {
+                    PathStep::Go(direction) => Some(direction.clone()),
+                    PathStep::Teleport(_) => None,
+                })
+                .collect()
+        }
+
+        /// Returns `true` if the path jumps between teleports.
+        pub fn uses_teleports(&self) -> bool {
+            self.steps
+                .iter()
+                .any(|step| matches!(step, PathStep::Teleport(_)))
+        }
+
+        /// Gets the tile the path ends on.
+        pub fn end(&self) -> Option<MapCoordinate> {
+            self.tiles.last().copied()
+        }
+
+        /// Returns `true` if the path has no steps, i.e. it starts where it ends.
+        pub fn is_empty(&self) -> bool {
+            self.steps.is_empty()
+        }
+
+        /// Returns the number of steps of the path.
+        pub fn len(&self) -> usize {
+            self.steps.len()
+        }
+    }
+
+    /// Returns the direction leading from `from` to the adjacent tile `to`.
+    pub(crate) fn direction_between(from: &MapCoordinate, to: &MapCoordinate) -> Option<Direction> {
+        from.offset_to(to).direction()
+    }
+
+    /// Returns the tiles sharing a side with `coordinate`, without checking the size of the map.
+    pub(crate) fn adjacent_tiles(coordinate: &MapCoordinate) -> Vec<MapCoordinate> {
+        [
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+            Direction::Up,
+        ]
+        .iter()
+        .filter_map(|direction| coordinate.checked_add(Offset::from(direction), usize::MAX))
+        .collect()
+    }
+
+    /// Returns the coordinates of the discovered teleports that have been activated, in reading order.
+    pub fn find_teleports(map: &[Vec<Option<Tile>>]) -> Vec<MapCoordinate> {
+        let mut teleports = vec![];
+        for (row, row_vector) in map.iter().enumerate() {
+            for (column, element) in row_vector.iter().enumerate() {
+                if let Some(Tile {
+                    tile_type: TileType::Teleport(true),
+                    ..
+                }) = element
+                {
+                    teleports.push(MapCoordinate::new(column, row));
+                }
+            }
+        }
+        teleports
+    }
+
+    /// The `PathFinder` struct computes A* paths over a map such as the one returned by `robot_map`.
+    ///
+    /// Entering a tile costs the cost of its `TileType`, plus the square of the elevation difference
+    /// when climbing. Tiles whose `TileType` cannot be walked on are never crossed.
+    /// From an activated teleport (`TileType::Teleport(true)`) the robot can also jump to any other
+    /// activated teleport, for the cost given in the options.
+    ///
+    /// A tile is undiscovered when the grid holds `None`, or when the `DiscoveryMask` given with
+    /// `with_discovered` does not mark it; the queries of `TileMapper` pass the mask of the source,
+    /// e.g. the one kept by a `ResourceIndex`.
+    /// The tiles given with `with_blocked` are never entered, e.g. where a `go` already failed.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::path::path_finder::{PathFinder, PathOptions};
+    ///
+    /// let map = robot_map(world).unwrap();
+    /// let finder = PathFinder::new(&map, PathOptions::default());
+    ///
+    /// if let Some(directions) = finder.find(start, target).and_then(|path| path.directions()) {
+    ///     for direction in directions {
+    ///         go(self, world, direction)?;
+    ///     }
+    /// }
+    /// ```
+    pub struct PathFinder<'a> {
+        map: &'a [Vec<Option<Tile>>],
+        options: PathOptions,
+        teleports: Vec<MapCoordinate>,
+        discovered: Option<&'a DiscoveryMask>,
+        blocked: Option<&'a HashSet<MapCoordinate>>,
+    }
+
+    impl<'a> PathFinder<'a> {
+        /// Creates a `PathFinder` over `map`.
+        pub fn new(map: &'a [Vec<Option<Tile>>], options: PathOptions) -> Self {
+            let teleports = match options.teleport_cost {
+                Some(_) => find_teleports(map),
+                None => vec![],
+            };
+            PathFinder {
+                map,
+                options,
+                teleports,
+                discovered: None,
+                blocked: None,
+            }
+        }
+
+        /// Creates a `PathFinder` over the grid of `source`, using its discovery mask if it keeps one.
+        pub fn for_source<S: MapSource + ?Sized>(
+            map: &'a [Vec<Option<Tile>>],
+            source: &'a S,
+            options: PathOptions,
+        ) -> Self {
+            let finder = PathFinder::new(map, options);
+            match source.discovered() {
+                Some(mask) => finder.with_discovered(mask),
+                None => finder,
+            }
+        }
+
+        /// Treats the tiles not marked in `mask` as undiscovered.
+        pub fn with_discovered(mut self, mask: &'a DiscoveryMask) -> Self {
+            self.discovered = Some(mask);
+            self
+        }
+
+        /// Never enters the tiles of `blocked`, whatever their type.
+        pub fn with_blocked(mut self, blocked: &'a HashSet<MapCoordinate>) -> Self {
+            self.blocked = Some(blocked);
+            self
+        }
+
+        /// Gets the activated teleports the path finder can jump between.
+        pub fn teleports(&self) -> &[MapCoordinate] {
+            &self.teleports
+        }
+
+        /// Returns the tiles reachable in one step from `current`, with the cost of the step.
+        fn neighbours(&self, current: &MapCoordinate) -> Vec<(MapCoordinate, usize)> {
+            let mut result: Vec<(MapCoordinate, usize)> = adjacent_tiles(current)
+                .into_iter()
+                .filter_map(|next| self.step_cost(current, &next).map(|cost| (next, cost)))
+                .collect();
+            if let Some(cost) = self.options.teleport_cost {
+                if self.teleports.contains(current) {
+                    result.extend(
+                        self.teleports
+                            .iter()
+                            .filter(|teleport| *teleport != current)
+                            .map(|teleport| (*teleport, cost)),
+                    );
+                }
+            }
+            result
+        }
+
+        /// Gets the tile at `coordinate`: `None` if it is out of the map, `Some(None)` if it is undiscovered.
+        fn tile(&self, coordinate: &MapCoordinate) -> Option<&'a Option<Tile>> {
+            let tile = self
+                .map
+                .get(coordinate.get_height())?
+                .get(coordinate.get_width())?;
+            match self.discovered {
+                Some(mask) if !mask.is_discovered(coordinate) => Some(&UNDISCOVERED),
+                _ => Some(tile),
+            }
+        }
+
+        /// Returns the energy needed to step from `from` to the adjacent tile `to`,
+        /// or `None` if `to` cannot be entered.
+        pub fn step_cost(&self, from: &MapCoordinate, to: &MapCoordinate) -> Option<usize> {
+            if self.blocked.is_some_and(|blocked| blocked.contains(to)) {
+                return None;
+            }
+            match self.tile(to)? {
+                Some(tile) => {
+                    if !tile.tile_type.properties().walk() {
+                        return None;
+                    }
+                    let mut cost = tile.tile_type.properties().cost();
+                    if self.options.elevation {
+                        if let Some(Some(current)) = self.tile(from) {
+                            if tile.elevation > current.elevation {
+                                cost += (tile.elevation - current.elevation).pow(2);
+                            }
+                        }
+                    }
+                    Some(self.scaled(cost))
+                }
+                None => match self.options.unknown_tiles {
+                    UnknownTiles::Blocked => None,
+                    UnknownTiles::Cost(cost) => Some(self.scaled(cost)),
+                },
+            }
+        }
+
+        /// Applies the weather multiplier to the cost of a move, rounding up.
+        fn scaled(&self, cost: usize) -> usize {
+            (cost as f64 * self.options.weather_multiplier).ceil() as usize
+        }
+
+        /// The smallest cost of a step on this map, used to keep the A* heuristic admissible.
+        ///
+        /// A teleport hop can cover any distance, so with two or more teleports the heuristic is disabled.
+        fn min_step_cost(&self) -> usize {
+            if self.teleports.len() > 1 {
+                return 0;
+            }
+            let known = self
+                .map
+                .iter()
+                .flatten()
+                .flatten()
+                .filter(|tile| tile.tile_type.properties().walk())
+                .map(|tile| tile.tile_type.properties().cost());
+            let unknown = match self.options.unknown_tiles {
+                UnknownTiles::Blocked => None,
+                UnknownTiles::Cost(cost) => Some(cost),
+            };
+            self.scaled(known.chain(unknown).min().unwrap_or(0))
+        }
+
+        /// Finds the cheapest path from `from` to `to`.
+        ///
+        /// Returns `None` if `to` cannot be reached.
+        pub fn find(&self, from: MapCoordinate, to: MapCoordinate) -> Option<Path> {
+            self.search(
+                from,
+                |c| *c == to,
+                |c| {
+                    c.get_width().abs_diff(to.get_width())
+                        + c.get_height().abs_diff(to.get_height())
+                },
+            )
+        }
+
+        /// Returns the energy needed to go from `from` to `to` along the cheapest path,
+        /// or `None` if `to` cannot be reached.
+        pub fn distance(&self, from: MapCoordinate, to: MapCoordinate) -> Option<usize> {
+            self.find(from, to).map(|path| path.cost)
+        }
+
+        /// Finds the cheapest path from `from` to any of `targets`.
+        ///
+        /// Returns `None` if none of them can be reached.
+        pub fn find_closest(&self, from: MapCoordinate, targets: &[MapCoordinate]) -> Option<Path> {
+            self.search(
+                from,
+                |c| targets.contains(c),
+                |c| {
+                    targets
+                        .iter()
+                        .map(|to| {
+                            c.get_width().abs_diff(to.get_width())
+                                + c.get_height().abs_diff(to.get_height())
+                        })
+                        .min()
+                        .unwrap_or(0)
+                },
+            )
+        }
+
+        /// Finds the cheapest path from `from` to a tile adjacent to `to`, e.g. to interact with the
+        /// content of `to` by calling `destroy` or `put`.
+        ///
+        /// Returns the path and the direction the robot must face at the end of it,
+        /// or `None` if no adjacent tile can be reached.
+        pub fn find_adjacent(
+            &self,
+            from: MapCoordinate,
+            to: MapCoordinate,
+        ) -> Option<(Path, Direction)> {
+            let path = self.search(
+                from,
+                |c| direction_between(c, &to).is_some(),
+                |c| {
+                    (c.get_width().abs_diff(to.get_width())
+                        + c.get_height().abs_diff(to.get_height()))
+                    .saturating_sub(1)
+                },
+            )?;
+            let end = path.end()?;
+            let direction = direction_between(&end, &to)?;
+            Some((path, direction))
+        }
+
+        /// A* search from `from` to the first tile satisfying `is_goal`, guided by `distance`,
+        /// a lower bound of the number of steps left.
+        fn search(
+            &self,
+            from: MapCoordinate,
+            is_goal: impl Fn(&MapCoordinate) -> bool,
+            distance: impl Fn(&MapCoordinate) -> usize,
+        ) -> Option<Path> {
+            self.tile(&from)?;
+            let min_step_cost = self.min_step_cost();
+
+            let mut costs: HashMap<MapCoordinate, usize> = HashMap::from([(from, 0)]);
+            let mut previous: HashMap<MapCoordinate, MapCoordinate> = HashMap::new();
+            let mut frontier =
+                BinaryHeap::from([Reverse((distance(&from) * min_step_cost, 0, from))]);
+
+            while let Some(Reverse((_, cost, current))) = frontier.pop() {
+                if costs
+                    .get(&current)
+                    .map(|best| cost > *best)
+                    .unwrap_or(false)
+                {
+                    // an outdated entry of a tile already reached more cheaply
+                    continue;
+                }
+                if is_goal(&current) {
+                    return Some(self.rebuild(from, current, cost, &previous));
+                }
+                for (next, step) in self.neighbours(&current) {
+                    let new_cost = cost + step;
+                    if costs.get(&next).map(|old| new_cost < *old).unwrap_or(true) {
+                        costs.insert(next, new_cost);
+                        previous.insert(next, current);
+                        frontier.push(Reverse((
+                            new_cost + distance(&next) * min_step_cost,
+                            new_cost,
+                            next,
+                        )));
+                    }
+                }
+            }
+            None
+        }
+
+        /// Walks back from `end` to `start` to build the path.
+        fn rebuild(
+            &self,
+            start: MapCoordinate,
+            end: MapCoordinate,
+            cost: usize,
+            previous: &HashMap<MapCoordinate, MapCoordinate>,
+        ) -> Path {
+            let mut tiles = vec![end];
+            let mut current = end;
+            while current != start {
+                current = previous[&current];
+                tiles.push(current);
+            }
+            tiles.reverse();
+            // steps between tiles that are not adjacent are teleport hops
+            let steps = tiles
+                .windows(2)
+                .map(|step| match direction_between(&step[0], &step[1]) {
+                    Some(direction) => PathStep::Go(direction),
+                    None => PathStep::Teleport(step[1]),
+                })
+                .collect();
+            Path { steps, tiles, cost }
+        }
+    }
+
+    impl TileMapper {
+        /// Finds the cheapest path from the Robot to a tile, over the tiles discovered so far.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World. It needs a tile grid.
+        /// * `origin` - The position distances are measured from, e.g. the robot or a `MapCoordinate`.
+        /// * `target` - The tile to reach, e.g. the result of `find_closest`.
+        /// * `options` - How undiscovered tiles and elevation are handled.
+        ///
+        /// # Returns
+        ///
+        /// Returns the path, whose steps can be performed one by one with `go` and `teleport`.
+        /// Returns an error if the world has not been discovered, `source` has no tile grid,
+        /// the target lies outside the map or cannot be reached.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::path::path_finder::PathOptions;
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// let target = mapper.find_closest(world, self, Content::Rock(0))?;
+        /// let path = mapper.find_path(world, self, target, PathOptions::default())?;
+        ///
+        /// for step in path.steps {
+        ///     match step {
+        ///         PathStep::Go(direction) => go(self, world, direction)?,
+        ///         PathStep::Teleport(to) => teleport(self, world, (to.get_height(), to.get_width()))?,
+        ///     };
+        /// }
+        /// ```
+        pub fn find_path<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            target: MapCoordinate,
+            options: PathOptions,
+        ) -> Result<Path, ToolError> {
+            match source.tiles() {
+                Some(map) => {
+                    if target.get_height() >= map.len() || target.get_width() >= map.len() {
+                        return Err(OutOfBounds {
+                            coordinate: target,
+                            size: map.len(),
+                        });
+                    }
+                    let origin_coordinates = origin.location();
+                    PathFinder::for_source(&map, source, options)
+                        .find(origin_coordinates, target)
+                        .ok_or(Unreachable(target))
+                }
+                None => Err(WorldNotDiscovered),
+            }
+        }
+
+        /// Finds the Tile containing a certain Content that is cheapest to reach from the Robot.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World. It needs a tile grid.
+        /// * `origin` - The position distances are measured from, e.g. the robot or a `MapCoordinate`.
+        /// * `content` - The Content we are looking for.
+        /// * `options` - How undiscovered tiles, elevation and teleports are handled.
+        ///
+        /// # Returns
+        ///
+        /// Returns the path to the tile, which ends on the tile itself.
+        /// Returns an error if no tile with the Content has been discovered or none can be reached.
+        ///
+        /// # Notes
+        ///
+        /// Unlike `find_closest`, distances are measured as the energy needed to walk there,
+        /// so a tile behind a teleport can be closer than a tile across a mountain.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::path::path_finder::PathOptions;
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// match mapper.find_closest_by_cost(world, robot, Content::Coin(0), PathOptions::default()) {
+        ///     Ok(path) => println!("{:?} in {} energy", path.end(), path.cost),
+        ///     Err(e) => println!("{}", e)
+        /// }
+        /// ```
+        pub fn find_closest_by_cost<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            content: Content,
+            options: PathOptions,
+        ) -> Result<Path, ToolError> {
+            let map = match source.tiles() {
+                Some(map) => map,
+                None => return Err(WorldNotDiscovered),
+            };
+            let targets: Vec<MapCoordinate> =
+                match collect_resources(&map).get(&discriminant(&content)) {
+                    Some(tiles) => tiles.iter().map(|(coordinate, _)| *coordinate).collect(),
+                    None => return Err(ContentNotDiscovered(discriminant(&content))),
+                };
+            let origin_coordinates = origin.location();
+            PathFinder::for_source(&map, source, options)
+                .find_closest(origin_coordinates, &targets)
+                .ok_or(ContentUnreachable(discriminant(&content)))
+        }
+    }
+}
@@ -0,0 +1,464 @@
+pub mod resource_prediction {
+    use std::collections::HashMap;
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::world::tile::{Content, Tile, TileType};
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::discovery::discovery_mask::DiscoveryMask;
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{ContentNotDiscovered, WorldNotDiscovered};
+    use crate::index::resource_index::ResourceIndex;
+    use crate::region::map_region::Rect;
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{all_contents, stored_amount, TileMapper};
+
+    /// How far from an undiscovered tile the tile types of its neighbours are looked at,
+    /// used by `PredictionModel::train`.
+    pub const DEFAULT_RADIUS: usize = 2;
+
+    /// How many discovered tiles the prior of a tile type is worth, used by `PredictionModel::train`.
+    pub const DEFAULT_PRIOR_WEIGHT: f64 = 1.;
+
+    /// What was seen on the discovered tiles of one tile type.
+    #[derive(Debug, Clone, Default)]
+    struct TypeStats {
+        tiles: usize,
+        contents: HashMap<Discriminant<Content>, usize>,
+    }
+
+    /// The `PredictionModel` struct estimates which content undiscovered tiles hold, from what
+    /// was found on the discovered ones.
+    ///
+    /// The type of an undiscovered tile is unknown, so it is guessed from the discovered tiles
+    /// around it: the probability of a content is the probability of finding it on every tile
+    /// type, e.g. fish in water or trees on grass, weighted by how often that type appears
+    /// nearby. When no tile nearby is discovered, the frequencies of the whole map are used.
+    ///
+    /// The frequencies of every tile type are smoothed with a prior, so that a kind of content
+    /// never discovered still gets a probability: with `count` tiles out of `tiles` holding it,
+    /// the probability is `(count + weight * prior) / (tiles + weight)`. `train` gives every kind of
+    /// content the same prior on every tile type, since robotics_lib does not tell where its world
+    /// generators put them; `train_with_priors` takes the priors to use instead, e.g. the frequencies
+    /// measured on other worlds. A weight of 0 ignores the priors.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::prediction::resource_prediction::PredictionModel;
+    ///
+    /// let robot_world = robot_map(world).unwrap();
+    /// let prediction = PredictionModel::train(&robot_world).predict(&robot_world, &Content::Fish(0));
+    ///
+    /// if let Some((coordinate, probability)) = prediction.explore_target(robot) {
+    ///     println!("{:.0}% chance of fish at {}", probability * 100., coordinate);
+    /// }
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct PredictionModel {
+        types: HashMap<Discriminant<TileType>, TypeStats>,
+        discovered: usize,
+        amounts: HashMap<Discriminant<Content>, (usize, usize)>,
+        radius: usize,
+        priors: HashMap<(Discriminant<TileType>, Discriminant<Content>), f64>,
+        // the prior of the pairs missing from `priors`
+        fallback_prior: f64,
+        prior_weight: f64,
+    }
+
+    impl PredictionModel {
+        /// Trains a model on the discovered tiles of a grid such as the one returned by `robot_map`,
+        /// with the same prior for every kind of content on every tile type.
+        pub fn train(robot_world: &[Vec<Option<Tile>>]) -> Self {
+            let kinds = all_contents().len() - 1;
+            let mut model = PredictionModel::train_with_priors(robot_world, &[]);
+            model.fallback_prior = 1. / kinds as f64;
+            model
+        }
+
+        /// Same as `train`, with the share of the tiles of every type assumed to hold every kind
+        /// of content before any is discovered, as `(tile_type, content, prior)`. Pairs missing from
+        /// `priors` have a prior of 0.
+        pub fn train_with_priors(
+            robot_world: &[Vec<Option<Tile>>],
+            priors: &[(TileType, Content, f64)],
+        ) -> Self {
+            let mut model = PredictionModel {
+                types: HashMap::new(),
+                discovered: 0,
+                amounts: HashMap::new(),
+                radius: DEFAULT_RADIUS,
+                priors: priors
+                    .iter()
+                    .map(|(tile_type, content, prior)| {
+                        ((discriminant(tile_type), discriminant(content)), *prior)
+                    })
+                    .collect(),
+                fallback_prior: 0.,
+                prior_weight: DEFAULT_PRIOR_WEIGHT,
+            };
+            for tile in robot_world.iter().flatten().flatten() {
+                let stats = model
+                    .types
+                    .entry(discriminant(&tile.tile_type))
+                    .or_default();
+                stats.tiles += 1;
+                model.discovered += 1;
+                if tile.content != Content::None {
+                    let kind = discriminant(&tile.content);
+                    *stats.contents.entry(kind).or_default() += 1;
+                    let (total, count) = model.amounts.entry(kind).or_default();
+                    *total += stored_amount(&tile.content.get_value());
+                    *count += 1;
+                }
+            }
+            model
+        }
+
+        /// Trains a model on the tiles read by the last update of `index`.
+        ///
+        /// Returns an error if the index was never updated with a grid of tiles.
+        pub fn from_index(index: &ResourceIndex) -> Result<Self, ToolError> {
+            index
+                .tiles()
+                .map(PredictionModel::train)
+                .ok_or(WorldNotDiscovered)
+        }
+
+        /// Sets how far from an undiscovered tile the types of its neighbours are looked at.
+        pub fn with_radius(mut self, radius: usize) -> Self {
+            self.radius = radius;
+            self
+        }
+
+        /// Sets the share of the tiles of type `tile_type` assumed to hold the same kind of `content`
+        /// before any is discovered.
+        pub fn with_prior(mut self, tile_type: &TileType, content: &Content, prior: f64) -> Self {
+            self.priors
+                .insert((discriminant(tile_type), discriminant(content)), prior);
+            self
+        }
+
+        /// Sets how many discovered tiles the priors are worth; 0 ignores them.
+        pub fn with_prior_weight(mut self, weight: f64) -> Self {
+            self.prior_weight = weight.max(0.);
+            self
+        }
+
+        /// Returns the probability that a tile of type `tile_type` holds the same kind of `content`:
+        /// the share of the discovered tiles of that type holding it, smoothed with the prior.
+        pub fn probability(&self, tile_type: &TileType, content: &Content) -> f64 {
+            self.type_probability(&discriminant(tile_type), &discriminant(content))
+        }
+
+        /// Returns the mean amount of the discovered tiles holding the same kind of `content`,
+        /// 1 if none was discovered.
+        pub fn mean_amount(&self, content: &Content) -> f64 {
+            self.kind_mean_amount(&discriminant(content))
+        }
+
+        /// Estimates the probability that the tile at `coordinate` holds the same kind of `content`.
+        ///
+        /// Discovered tiles have a probability of 1 if they hold it and 0 otherwise.
+        pub fn estimate(
+            &self,
+            robot_world: &[Vec<Option<Tile>>],
+            coordinate: &MapCoordinate,
+            content: &Content,
+        ) -> f64 {
+            self.estimate_kind(robot_world, coordinate, &discriminant(content))
+        }
+
+        /// Estimates the probability of every kind of content that can be on the tile at
+        /// `coordinate`, from the most to the least likely.
+        pub fn distribution(
+            &self,
+            robot_world: &[Vec<Option<Tile>>],
+            coordinate: &MapCoordinate,
+        ) -> Vec<(Discriminant<Content>, f64)> {
+            let mut distribution: Vec<(Discriminant<Content>, f64)> = all_contents()
+                .iter()
+                .filter(|content| **content != Content::None)
+                .map(|content| {
+                    let kind = discriminant(content);
+                    (kind, self.estimate_kind(robot_world, coordinate, &kind))
+                })
+                .filter(|(_, probability)| *probability > 0.)
+                .collect();
+            distribution.sort_by(|a, b| b.1.total_cmp(&a.1));
+            distribution
+        }
+
+        /// Estimates the probability and the expected quantity of `content` on every tile.
+        pub fn predict(&self, robot_world: &[Vec<Option<Tile>>], content: &Content) -> Prediction {
+            let mean = self.mean_amount(content);
+            let size = robot_world.len();
+            let probabilities: Vec<Vec<f64>> = (0..size)
+                .map(|row| {
+                    (0..size)
+                        .map(|column| {
+                            self.estimate(robot_world, &MapCoordinate::new(column, row), content)
+                        })
+                        .collect()
+                })
+                .collect();
+            let expected = (0..size)
+                .map(|row| {
+                    (0..size)
+                        .map(|column| {
+                            let coordinate = MapCoordinate::new(column, row);
+                            let probability = probabilities[row][column];
+                            match tile_at(robot_world, &coordinate) {
+                                Some(tile) if probability > 0. => {
+                                    stored_amount(&tile.content.get_value()) as f64
+                                }
+                                Some(_) => 0.,
+                                None => probability * mean,
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            Prediction {
+                discovered: DiscoveryMask::from_tiles(robot_world),
+                probabilities,
+                expected,
+            }
+        }
+
+        fn estimate_kind(
+            &self,
+            robot_world: &[Vec<Option<Tile>>],
+            coordinate: &MapCoordinate,
+            kind: &Discriminant<Content>,
+        ) -> f64 {
+            match tile_at(robot_world, coordinate) {
+                Some(tile)
+                    if tile.content != Content::None && discriminant(&tile.content) == *kind =>
+                {
+                    1.
+                }
+                Some(_) => 0.,
+                None => self
+                    .type_weights(robot_world, coordinate)
+                    .iter()
+                    .map(|(tile_type, weight)| weight * self.type_probability(tile_type, kind))
+                    .sum(),
+            }
+        }
+
+        fn type_probability(
+            &self,
+            tile_type: &Discriminant<TileType>,
+            kind: &Discriminant<Content>,
+        ) -> f64 {
+            let (count, tiles) = self.types.get(tile_type).map_or((0, 0), |stats| {
+                (stats.contents.get(kind).copied().unwrap_or(0), stats.tiles)
+            });
+            let prior = self
+                .priors
+                .get(&(*tile_type, *kind))
+                .copied()
+                .unwrap_or(self.fallback_prior);
+            let weight = self.prior_weight;
+            if tiles == 0 && weight == 0. {
+                return 0.;
+            }
+            (count as f64 + weight * prior) / (tiles as f64 + weight)
+        }
+
+        fn kind_mean_amount(&self, kind: &Discriminant<Content>) -> f64 {
+            match self.amounts.get(kind) {
+                Some((total, count)) if *count > 0 => *total as f64 / *count as f64,
+                // a single unit is assumed until the content is discovered
+                _ => 1.,
+            }
+        }
+
+        /// Returns how likely the tile at `coordinate` is of every tile type, from the discovered
+        /// tiles around it, or from the whole map if none is discovered.
+        fn type_weights(
+            &self,
+            robot_world: &[Vec<Option<Tile>>],
+            coordinate: &MapCoordinate,
+        ) -> Vec<(Discriminant<TileType>, f64)> {
+            let mut counts: HashMap<Discriminant<TileType>, usize> = HashMap::new();
+            for neighbour in Rect::around(*coordinate, self.radius, robot_world.len())
+                .into_iter()
+                .flat_map(|region| region.iter())
+            {
+                if let Some(tile) = tile_at(robot_world, &neighbour) {
+                    *counts.entry(discriminant(&tile.tile_type)).or_default() += 1;
+                }
+            }
+            let (counts, total) = if counts.is_empty() {
+                (
+                    self.types
+                        .iter()
+                        .map(|(tile_type, stats)| (*tile_type, stats.tiles))
+                        .collect(),
+                    self.discovered,
+                )
+            } else {
+                let total = counts.values().sum();
+                (counts, total)
+            };
+            counts
+                .into_iter()
+                .map(|(tile_type, count)| (tile_type, count as f64 / total.max(1) as f64))
+                .collect()
+        }
+    }
+
+    /// Returns the tile at `coordinate`, if it was discovered.
+    fn tile_at<'a>(
+        robot_world: &'a [Vec<Option<Tile>>],
+        coordinate: &MapCoordinate,
+    ) -> Option<&'a Tile> {
+        robot_world
+            .get(coordinate.get_height())?
+            .get(coordinate.get_width())?
+            .as_ref()
+    }
+
+    /// The estimates of `PredictionModel::predict` for one kind of content, indexed by row and
+    /// then by column.
+    #[derive(Debug, Clone)]
+    pub struct Prediction {
+        probabilities: Vec<Vec<f64>>,
+        expected: Vec<Vec<f64>>,
+        discovered: DiscoveryMask,
+    }
+
+    impl Prediction {
+        /// Gets the grid of probabilities.
+        pub fn probabilities(&self) -> &[Vec<f64>] {
+            &self.probabilities
+        }
+
+        /// Gets the probability that the tile at `coordinate` holds the content, 0 outside the map.
+        pub fn probability(&self, coordinate: &MapCoordinate) -> f64 {
+            cell(&self.probabilities, coordinate)
+        }
+
+        /// Gets the expected quantity on the tile at `coordinate`: the actual amount on discovered
+        /// tiles, the probability times the mean amount found so far on the others.
+        pub fn expected_quantity(&self, coordinate: &MapCoordinate) -> f64 {
+            cell(&self.expected, coordinate)
+        }
+
+        /// Returns the quantity expected on the undiscovered tiles of the whole map.
+        pub fn expected_undiscovered(&self) -> f64 {
+            self.undiscovered()
+                .map(|coordinate| self.expected_quantity(&coordinate))
+                .sum()
+        }
+
+        /// Returns the undiscovered tile most likely to hold the content, with its probability,
+        /// to guide exploration.
+        ///
+        /// In case of two tiles with the same probability, the closest one to `origin` is returned,
+        /// then the first one in reading order. Returns `None` if no undiscovered tile can hold it.
+        pub fn explore_target<K>(
+            &self,
+            origin: &impl Locatable<K>,
+        ) -> Option<(MapCoordinate, f64)> {
+            let origin = origin.location();
+            self.undiscovered()
+                .map(|coordinate| (coordinate, self.probability(&coordinate)))
+                .filter(|(_, probability)| *probability > 0.)
+                .fold(None, |best, (coordinate, probability)| match best {
+                    Some((old, old_probability))
+                        if old_probability > probability
+                            || (old_probability == probability
+                                && old.get_distance(&origin)
+                                    <= coordinate.get_distance(&origin)) =>
+                    {
+                        best
+                    }
+                    _ => Some((coordinate, probability)),
+                })
+        }
+
+        fn undiscovered(&self) -> impl Iterator<Item = MapCoordinate> + '_ {
+            Rect::whole(self.discovered.size())
+                .into_iter()
+                .flat_map(|map| map.iter())
+                .filter(|coordinate| !self.discovered.is_discovered(coordinate))
+        }
+    }
+
+    fn cell(grid: &[Vec<f64>], coordinate: &MapCoordinate) -> f64 {
+        grid.get(coordinate.get_height())
+            .and_then(|row| row.get(coordinate.get_width()))
+            .copied()
+            .unwrap_or(0.)
+    }
+
+    /// Where to look for a content, returned by `find_or_predict`.
+    ///
+    /// ## Variants
+    ///
+    /// - `Discovered`: the closest discovered tile holding the content.
+    /// - `Predicted`: no discovered tile holds it; the undiscovered tile most likely to hold it, with its probability.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Lead {
+        Discovered(MapCoordinate),
+        Predicted {
+            coordinate: MapCoordinate,
+            probability: f64,
+        },
+    }
+
+    impl TileMapper {
+        /// Same as `find_closest`, but when no discovered tile holds `content` the undiscovered tile
+        /// of `source` most likely to hold it according to `model` is returned instead.
+        ///
+        /// # Notes
+        ///
+        /// A model trained on `source` itself predicts a content it has not discovered from its priors
+        /// only, the same on every tile type unless given to `train_with_priors`: train it on a larger map, e.g. the whole index when `source` is
+        /// restricted to a region, or on a world explored before, to learn where it actually is.
+        ///
+        /// # Returns
+        ///
+        /// Returns a `Lead` telling whether the tile was discovered or predicted.
+        /// Returns `ContentNotDiscovered` if the source has no grid of tiles or no undiscovered
+        /// tile can hold `content`.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::prediction::resource_prediction::Lead;
+        ///
+        /// let model = PredictionModel::from_index(&index)?;
+        ///
+        /// match mapper.find_or_predict(&world.within(region), robot, Content::Market(0), &model)? {
+        ///     Lead::Discovered(market) => // go there,
+        ///     Lead::Predicted { coordinate, .. } => // explore towards it,
+        /// }
+        /// ```
+        pub fn find_or_predict<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            content: Content,
+            model: &PredictionModel,
+        ) -> Result<Lead, ToolError> {
+            match self.find_closest(source, origin, content.clone()) {
+                Ok(coordinate) => Ok(Lead::Discovered(coordinate)),
+                Err(ContentNotDiscovered(kind)) => {
+                    let robot_world = source.tiles().ok_or(ContentNotDiscovered(kind))?;
+                    model
+                        .predict(&robot_world, &content)
+                        .explore_target(origin)
+                        .map(|(coordinate, probability)| Lead::Predicted {
+                            coordinate,
+                            probability,
+                        })
+                        .ok_or(ContentNotDiscovered(kind))
+                }
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
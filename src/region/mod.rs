@@ -0,0 +1,336 @@
+pub mod map_region {
+    use std::borrow::Cow;
+    use std::fmt::{Display, Formatter};
+
+    use robotics_lib::interface::Direction;
+    use robotics_lib::world::tile::{Content, Tile};
+
+    use crate::coordinates::map_coordinate::{MapCoordinate, Offset};
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{ResourceEntry, ResourceMap};
+
+    /// A rectangular region of the map, borders included.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use tile_resource_mapper_tool::coordinates::map_coordinate::MapCoordinate;
+    /// use tile_resource_mapper_tool::region::map_region::Rect;
+    ///
+    /// let region = Rect::new(MapCoordinate::new(4, 1), MapCoordinate::new(2, 3));
+    /// assert_eq!(region.area(), 9);
+    /// assert!(region.contains(&MapCoordinate::new(3, 3)));
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Rect {
+        top_left: MapCoordinate,
+        bottom_right: MapCoordinate,
+    }
+
+    impl Rect {
+        /// Creates the smallest region containing both corners, given in any order.
+        pub fn new(a: MapCoordinate, b: MapCoordinate) -> Self {
+            Rect {
+                top_left: MapCoordinate::new(
+                    a.get_width().min(b.get_width()),
+                    a.get_height().min(b.get_height()),
+                ),
+                bottom_right: MapCoordinate::new(
+                    a.get_width().max(b.get_width()),
+                    a.get_height().max(b.get_height()),
+                ),
+            }
+        }
+
+        /// Creates the region covering a whole map whose side is `size` tiles long.
+        ///
+        /// Returns `None` for an empty map.
+        pub fn whole(size: usize) -> Option<Self> {
+            let last = size.checked_sub(1)?;
+            Some(Rect::new(
+                MapCoordinate::new(0, 0),
+                MapCoordinate::new(last, last),
+            ))
+        }
+
+        /// Creates the square region of tiles at most `radius` steps away from `center`
+        /// (diagonal steps included), clipped to a map whose side is `size` tiles long.
+        ///
+        /// Returns `None` if `center` is outside the map.
+        pub fn around(center: MapCoordinate, radius: usize, size: usize) -> Option<Self> {
+            if !center.is_within(size) {
+                return None;
+            }
+            let radius = radius.min(isize::MAX as usize) as isize;
+            Some(Rect::new(
+                center.saturating_sub(Offset::new(radius, radius), size),
+                center.saturating_add(Offset::new(radius, radius), size),
+            ))
+        }
+
+        /// Gets the top left corner.
+        pub fn top_left(&self) -> MapCoordinate {
+            self.top_left
+        }
+
+        /// Gets the bottom right corner.
+        pub fn bottom_right(&self) -> MapCoordinate {
+            self.bottom_right
+        }
+
+        /// Gets the number of columns of the region.
+        pub fn width(&self) -> usize {
+            self.bottom_right.get_width() - self.top_left.get_width() + 1
+        }
+
+        /// Gets the number of rows of the region.
+        pub fn height(&self) -> usize {
+            self.bottom_right.get_height() - self.top_left.get_height() + 1
+        }
+
+        /// Gets the number of tiles of the region.
+        pub fn area(&self) -> usize {
+            self.width() * self.height()
+        }
+
+        /// Checks whether `coordinate` lies inside the region.
+        pub fn contains(&self, coordinate: &MapCoordinate) -> bool {
+            (self.top_left.get_width()..=self.bottom_right.get_width())
+                .contains(&coordinate.get_width())
+                && (self.top_left.get_height()..=self.bottom_right.get_height())
+                    .contains(&coordinate.get_height())
+        }
+
+        /// Returns the tiles both regions have in common, or `None` if they do not overlap.
+        pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+            let top_left = MapCoordinate::new(
+                self.top_left.get_width().max(other.top_left.get_width()),
+                self.top_left.get_height().max(other.top_left.get_height()),
+            );
+            let bottom_right = MapCoordinate::new(
+                self.bottom_right
+                    .get_width()
+                    .min(other.bottom_right.get_width()),
+                self.bottom_right
+                    .get_height()
+                    .min(other.bottom_right.get_height()),
+            );
+            if top_left.get_width() > bottom_right.get_width()
+                || top_left.get_height() > bottom_right.get_height()
+            {
+                None
+            } else {
+                Some(Rect {
+                    top_left,
+                    bottom_right,
+                })
+            }
+        }
+
+        /// Clips the region to a map whose side is `size` tiles long.
+        pub fn clip(&self, size: usize) -> Option<Rect> {
+            self.intersection(&Rect::whole(size)?)
+        }
+
+        /// Iterates over the tiles of the region in reading order.
+        pub fn iter(&self) -> impl Iterator<Item = MapCoordinate> {
+            let columns = self.top_left.get_width()..=self.bottom_right.get_width();
+            (self.top_left.get_height()..=self.bottom_right.get_height()).flat_map(move |row| {
+                columns
+                    .clone()
+                    .map(move |column| MapCoordinate::new(column, row))
+            })
+        }
+    }
+
+    impl Display for Rect {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}..={}", self.top_left, self.bottom_right)
+        }
+    }
+
+    /// The iterator returned by `MapCoordinate::line_to`.
+    ///
+    /// It walks Bresenham's line from the start to the end, both included.
+    pub struct Line {
+        current: Option<MapCoordinate>,
+        end: MapCoordinate,
+        step: Offset,
+        delta: Offset,
+        error: isize,
+    }
+
+    impl Iterator for Line {
+        type Item = MapCoordinate;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let current = self.current?;
+            if current == self.end {
+                self.current = None;
+                return Some(current);
+            }
+            let (dw, dh) = (self.delta.get_width(), self.delta.get_height());
+            let double = 2 * self.error;
+            let mut step = (0, 0);
+            if double >= dh {
+                self.error += dh;
+                step.0 = self.step.get_width();
+            }
+            if double <= dw {
+                self.error += dw;
+                step.1 = self.step.get_height();
+            }
+            // the line never leaves the rectangle between its ends, so the step cannot underflow
+            self.current = current.checked_add(Offset::from(step), usize::MAX);
+            Some(current)
+        }
+    }
+
+    impl MapCoordinate {
+        /// Iterates over the tiles sharing a side with this one, clipped to a map whose side
+        /// is `size` tiles long, in reading order.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use tile_resource_mapper_tool::coordinates::map_coordinate::MapCoordinate;
+        /// let corner = MapCoordinate::new(0, 0);
+        /// let neighbours: Vec<MapCoordinate> = corner.neighbours(5).collect();
+        /// assert_eq!(neighbours, vec![MapCoordinate::new(1, 0), MapCoordinate::new(0, 1)]);
+        /// ```
+        pub fn neighbours(&self, size: usize) -> impl Iterator<Item = MapCoordinate> {
+            let origin = *self;
+            [
+                Direction::Up,
+                Direction::Left,
+                Direction::Right,
+                Direction::Down,
+            ]
+            .into_iter()
+            .filter_map(move |direction| origin.checked_add(Offset::from(direction), size))
+        }
+
+        /// Iterates over the tiles sharing a side or a corner with this one, clipped to a map
+        /// whose side is `size` tiles long, in reading order.
+        pub fn neighbours8(&self, size: usize) -> impl Iterator<Item = MapCoordinate> {
+            self.ring(1, size)
+        }
+
+        /// Iterates over the tiles exactly `radius` steps away from this one, diagonal steps
+        /// included, clipped to a map whose side is `size` tiles long, in reading order.
+        ///
+        /// A radius of 0 gives the tile itself.
+        pub fn ring(&self, radius: usize, size: usize) -> impl Iterator<Item = MapCoordinate> {
+            let center = *self;
+            Rect::around(center, radius, size)
+                .into_iter()
+                .flat_map(|region| region.iter())
+                .filter(move |c| {
+                    let offset = center.offset_to(c);
+                    offset
+                        .get_width()
+                        .unsigned_abs()
+                        .max(offset.get_height().unsigned_abs())
+                        == radius
+                })
+        }
+
+        /// Iterates over the tiles on the straight line from this tile to `end`, both included.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use tile_resource_mapper_tool::coordinates::map_coordinate::MapCoordinate;
+        /// let line: Vec<MapCoordinate> = MapCoordinate::new(0, 0).line_to(MapCoordinate::new(2, 1)).collect();
+        /// assert_eq!(line.len(), 3);
+        /// assert_eq!(line[2], MapCoordinate::new(2, 1));
+        /// ```
+        pub fn line_to(&self, end: MapCoordinate) -> Line {
+            let offset = self.offset_to(&end);
+            let delta = Offset::new(offset.get_width().abs(), -offset.get_height().abs());
+            Line {
+                current: Some(*self),
+                end,
+                step: Offset::new(offset.get_width().signum(), offset.get_height().signum()),
+                delta,
+                error: delta.get_width() + delta.get_height(),
+            }
+        }
+    }
+
+    /// A `MapSource` restricted to a region, returned by `MapSource::within`.
+    ///
+    /// Tiles outside the region are treated as undiscovered, so every query run on it only
+    /// considers the tiles inside the region.
+    ///
+    /// Only `visit` filters the tiles lazily, so `find_closest`, `find_most_loaded` and `find_container`
+    /// cost no more than on the source. `resources` and `tiles` allocate a filtered copy of the whole
+    /// source at every call, because a grid keeps its size and its coordinates: the queries reading
+    /// them, like path finding or clustering, cost more on a restricted source than on the source itself.
+    pub struct Restricted<'a, S: MapSource + ?Sized> {
+        source: &'a S,
+        region: Rect,
+    }
+
+    impl<'a, S: MapSource + ?Sized> Restricted<'a, S> {
+        /// Restricts `source` to `region`.
+        pub fn new(source: &'a S, region: Rect) -> Self {
+            Restricted { source, region }
+        }
+
+        /// Gets the region the source is restricted to.
+        pub fn region(&self) -> Rect {
+            self.region
+        }
+    }
+
+    impl<'a, S: MapSource + ?Sized> MapSource for Restricted<'a, S> {
+        // copies the tiles of the source inside the region
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            let resources = self.source.resources()?;
+            let restricted: ResourceMap = resources
+                .iter()
+                .filter_map(|(kind, tiles)| {
+                    let inside: Vec<_> = tiles
+                        .iter()
+                        .filter(|(coordinate, _)| self.region.contains(coordinate))
+                        .cloned()
+                        .collect();
+                    (!inside.is_empty()).then_some((*kind, inside))
+                })
+                .collect();
+            Some(Cow::Owned(restricted))
+        }
+
+        // copies the whole grid of the source, with `None` outside the region
+        fn tiles(&self) -> Option<Cow<'_, [Vec<Option<Tile>>]>> {
+            let tiles = self.source.tiles()?;
+            let restricted: Vec<Vec<Option<Tile>>> = tiles
+                .iter()
+                .enumerate()
+                .map(|(row, row_vector)| {
+                    row_vector
+                        .iter()
+                        .enumerate()
+                        .map(|(column, tile)| {
+                            if self.region.contains(&MapCoordinate::new(column, row)) {
+                                tile.clone()
+                            } else {
+                                None
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            Some(Cow::Owned(restricted))
+        }
+
+        fn visit(&self, content: &Content, visitor: &mut dyn FnMut(ResourceEntry<'_>)) -> bool {
+            self.source.visit(content, &mut |entry| {
+                if self.region.contains(&entry.coordinate()) {
+                    visitor(entry)
+                }
+            })
+        }
+    }
+}
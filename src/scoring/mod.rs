@@ -0,0 +1,167 @@
+pub mod target_scoring {
+    use std::mem::discriminant;
+
+    use robotics_lib::world::tile::Content;
+
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{ContentNotDiscovered, WorldNotDiscovered};
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{stored_amount, ResourceMap, TileMapper};
+
+    /// The weights used to score the tiles holding a resource.
+    ///
+    /// The score of a tile is
+    /// `(quantity * quantity_weight + neighbourhood_quantity * neighbourhood_weight) / (base_cost + distance * travel_weight)`,
+    /// i.e. how much can be collected per unit of travel cost.
+    ///
+    /// ## Fields
+    ///
+    /// - `quantity_weight`: The value of a unit of content on the tile itself.
+    /// - `travel_weight`: The cost of moving by one tile.
+    /// - `base_cost`: A fixed cost added to every trip, which also avoids dividing by zero on the robot's tile.
+    /// - `neighbourhood_radius`: The radius within which other tiles with the same content give a bonus, 0 to disable it.
+    /// - `neighbourhood_weight`: The value of a unit of content on the neighbouring tiles.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct ScoringWeights {
+        pub quantity_weight: f64,
+        pub travel_weight: f64,
+        pub base_cost: f64,
+        pub neighbourhood_radius: f64,
+        pub neighbourhood_weight: f64,
+    }
+
+    impl Default for ScoringWeights {
+        fn default() -> Self {
+            ScoringWeights {
+                quantity_weight: 1.,
+                travel_weight: 1.,
+                base_cost: 1.,
+                neighbourhood_radius: 0.,
+                neighbourhood_weight: 0.,
+            }
+        }
+    }
+
+    /// A tile ranked by `rank_targets`, with the figures its score was computed from.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ScoredTarget {
+        pub coordinate: MapCoordinate,
+        pub quantity: usize,
+        pub neighbourhood_quantity: usize,
+        pub distance: f64,
+        pub travel_cost: f64,
+        pub score: f64,
+    }
+
+    impl TileMapper {
+        /// Scores every tile of `resources` holding the same kind of `content`, as seen from `origin`.
+        ///
+        /// Returns the tiles sorted from the best to the worst score; tiles with the same score
+        /// are sorted by distance. Returns an empty vector if no tile with `content` is in the map.
+        pub fn score_targets(
+            resources: &ResourceMap,
+            content: &Content,
+            origin: MapCoordinate,
+            weights: &ScoringWeights,
+        ) -> Vec<ScoredTarget> {
+            let tiles = match resources.get(&discriminant(content)) {
+                Some(tiles) => tiles,
+                None => return vec![],
+            };
+
+            let mut targets: Vec<ScoredTarget> = tiles
+                .iter()
+                .map(|(coordinate, quantity)| {
+                    let quantity = stored_amount(quantity);
+                    let neighbourhood_quantity = if weights.neighbourhood_radius > 0. {
+                        tiles
+                            .iter()
+                            .filter(|(c, _)| {
+                                c != coordinate
+                                    && c.get_distance(coordinate) <= weights.neighbourhood_radius
+                            })
+                            .map(|(_, q)| stored_amount(q))
+                            .sum()
+                    } else {
+                        0
+                    };
+                    let distance = coordinate.get_distance(&origin);
+                    let travel_cost = weights.base_cost + distance * weights.travel_weight;
+                    let value = quantity as f64 * weights.quantity_weight
+                        + neighbourhood_quantity as f64 * weights.neighbourhood_weight;
+                    ScoredTarget {
+                        coordinate: *coordinate,
+                        quantity,
+                        neighbourhood_quantity,
+                        distance,
+                        travel_cost,
+                        score: value / travel_cost,
+                    }
+                })
+                .collect();
+            targets.sort_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then(a.distance.total_cmp(&b.distance))
+                    .then(a.coordinate.cmp(&b.coordinate))
+            });
+            targets
+        }
+
+        /// Ranks the tiles containing a certain Content by quantity per unit of travel cost.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `origin` - The position distances are measured from, e.g. the robot or a `MapCoordinate`.
+        /// * `content` - The Content we're searching for.
+        /// * `weights` - The weights used to compute the score of every tile.
+        ///
+        /// # Returns
+        ///
+        /// Returns every tile with the requested Content, from the best to the worst score.
+        /// Returns an error if no tile is found.
+        ///
+        /// # Notes
+        ///
+        /// Unlike `find_most_loaded`, a slightly richer tile on the other side of the map
+        /// is ranked below a nearby one.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::scoring::target_scoring::ScoringWeights;
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// let result = mapper.rank_targets(world, robot, Content::Rock(0), &ScoringWeights::default());
+        ///
+        /// match result {
+        ///     Ok(targets) => println!("best: {} (score {})", targets[0].coordinate, targets[0].score),
+        ///     Err(e) => println!("{}", e)
+        /// }
+        /// ```
+        pub fn rank_targets<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            content: Content,
+            weights: &ScoringWeights,
+        ) -> Result<Vec<ScoredTarget>, ToolError> {
+            match source.resources() {
+                Some(map) => {
+                    let origin_coordinates = origin.location();
+                    let targets =
+                        TileMapper::score_targets(&map, &content, origin_coordinates, weights);
+                    if targets.is_empty() {
+                        Err(ContentNotDiscovered(discriminant(&content)))
+                    } else {
+                        Ok(targets)
+                    }
+                }
+                None => Err(WorldNotDiscovered),
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+pub mod map_source {
+    use std::borrow::Cow;
+
+    use robotics_lib::interface::robot_map;
+    use robotics_lib::world::tile::{Content, Tile};
+    use robotics_lib::world::World;
+
+    use crate::discovery::discovery_mask::DiscoveryMask;
+    use crate::history::resource_history::HistoryReplayer;
+    use crate::index::resource_index::ResourceIndex;
+    use crate::region::map_region::{Rect, Restricted};
+    use crate::spatial::spatial_index::SpatialIndex;
+    use crate::tool::tile_mapper::{
+        collect_resources, grid_entries, map_entries, ResourceEntry, ResourceMap,
+    };
+
+    /// Anything the queries of `TileMapper` can read the discovered resources from.
+    ///
+    /// Every query is generic over `MapSource`, so the same call works with the `World`,
+    /// a grid returned by `robot_map`, a `ResourceIndex` or a map rebuilt by a `HistoryReplayer`.
+    ///
+    /// The `World` is the only source that copies: robotics_lib hands out its discovered tiles only
+    /// through `robot_map`, which clones the whole grid at every query. When several queries run in
+    /// the same tick, call `robot_map` once and pass `&grid`, or keep a `ResourceIndex`.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+    ///
+    /// let mapper = TileMapper{};
+    ///
+    /// let from_world = mapper.find_closest(world, robot, Content::Rock(0));
+    /// let from_grid = mapper.find_closest(&robot_map(world).unwrap(), robot, Content::Rock(0));
+    /// let from_index = mapper.find_closest(&index, robot, Content::Rock(0));
+    /// ```
+    pub trait MapSource {
+        /// Returns every discovered tile grouped by the kind of its `Content`,
+        /// or `None` if nothing has been discovered yet.
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>>;
+
+        /// Returns the grid of discovered tiles, indexed by row and then by column.
+        ///
+        /// Sources that only know where the resources are, such as a replayed map,
+        /// return `None`: queries needing the tile types, like path finding, cannot use them.
+        fn tiles(&self) -> Option<Cow<'_, [Vec<Option<Tile>>]>> {
+            None
+        }
+
+        /// Returns the spatial index of the source, if it keeps one, so that `find_closest`
+        /// does not need to scan every tile.
+        fn spatial(&self) -> Option<&SpatialIndex> {
+            None
+        }
+
+        /// Returns the mask of the tiles discovered so far, if the source keeps one, so that
+        /// path finding can tell undiscovered tiles without reading the grid.
+        fn discovered(&self) -> Option<&DiscoveryMask> {
+            None
+        }
+
+        /// Calls `visitor` on every discovered tile holding the same kind of `content`,
+        /// borrowing the tiles instead of copying them into a `ResourceMap`.
+        ///
+        /// Returns `false` if nothing has been discovered yet.
+        ///
+        /// Queries looking for a single tile, like `find_closest`, walk the source this way,
+        /// so they do not allocate when the source is borrowed.
+        fn visit(&self, content: &Content, visitor: &mut dyn FnMut(ResourceEntry<'_>)) -> bool {
+            match self.resources() {
+                Some(resources) => {
+                    map_entries(&resources, content).for_each(visitor);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        /// Restricts the source to `region`, so that queries only consider the tiles inside it.
+        ///
+        /// The queries needing the whole resource map or the grid copy the source, see `Restricted`.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::region::map_region::Rect;
+        ///
+        /// let region = Rect::around(robot_position, 10, size).unwrap();
+        /// let rock = mapper.find_closest(&world.within(region), robot, Content::Rock(0))?;
+        /// ```
+        fn within(&self, region: Rect) -> Restricted<'_, Self> {
+            Restricted::new(self, region)
+        }
+    }
+
+    impl<T: MapSource + ?Sized> MapSource for &T {
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            (**self).resources()
+        }
+
+        fn tiles(&self) -> Option<Cow<'_, [Vec<Option<Tile>>]>> {
+            (**self).tiles()
+        }
+
+        fn spatial(&self) -> Option<&SpatialIndex> {
+            (**self).spatial()
+        }
+
+        fn discovered(&self) -> Option<&DiscoveryMask> {
+            (**self).discovered()
+        }
+
+        fn visit(&self, content: &Content, visitor: &mut dyn FnMut(ResourceEntry<'_>)) -> bool {
+            (**self).visit(content, visitor)
+        }
+    }
+
+    /// Every method copies the discovered grid with `robot_map`, see `MapSource`.
+    impl MapSource for World {
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            robot_map(self).map(|robot_world| Cow::Owned(collect_resources(&robot_world)))
+        }
+
+        fn tiles(&self) -> Option<Cow<'_, [Vec<Option<Tile>>]>> {
+            robot_map(self).map(Cow::Owned)
+        }
+
+        fn visit(&self, content: &Content, visitor: &mut dyn FnMut(ResourceEntry<'_>)) -> bool {
+            // `robot_map` copies the grid, but the tiles are not grouped in a map
+            match robot_map(self) {
+                Some(robot_world) => robot_world.as_slice().visit(content, visitor),
+                None => false,
+            }
+        }
+    }
+
+    impl MapSource for [Vec<Option<Tile>>] {
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            Some(Cow::Owned(collect_resources(self)))
+        }
+
+        fn tiles(&self) -> Option<Cow<'_, [Vec<Option<Tile>>]>> {
+            Some(Cow::Borrowed(self))
+        }
+
+        fn visit(&self, content: &Content, visitor: &mut dyn FnMut(ResourceEntry<'_>)) -> bool {
+            grid_entries(self, content).for_each(visitor);
+            true
+        }
+    }
+
+    impl MapSource for Vec<Vec<Option<Tile>>> {
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            self.as_slice().resources()
+        }
+
+        fn tiles(&self) -> Option<Cow<'_, [Vec<Option<Tile>>]>> {
+            self.as_slice().tiles()
+        }
+
+        fn visit(&self, content: &Content, visitor: &mut dyn FnMut(ResourceEntry<'_>)) -> bool {
+            self.as_slice().visit(content, visitor)
+        }
+    }
+
+    impl MapSource for ResourceMap {
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            Some(Cow::Borrowed(self))
+        }
+    }
+
+    impl MapSource for ResourceIndex {
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            Some(Cow::Borrowed(ResourceIndex::resources(self)))
+        }
+
+        fn tiles(&self) -> Option<Cow<'_, [Vec<Option<Tile>>]>> {
+            ResourceIndex::tiles(self).map(Cow::Borrowed)
+        }
+
+        fn spatial(&self) -> Option<&SpatialIndex> {
+            Some(ResourceIndex::spatial(self))
+        }
+
+        fn discovered(&self) -> Option<&DiscoveryMask> {
+            Some(ResourceIndex::discovered(self))
+        }
+    }
+
+    impl MapSource for HistoryReplayer {
+        fn resources(&self) -> Option<Cow<'_, ResourceMap>> {
+            Some(Cow::Borrowed(self.state()))
+        }
+    }
+}
@@ -0,0 +1,327 @@
+pub mod spatial_index {
+    use std::collections::HashMap;
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::world::tile::Content;
+
+    use crate::coordinates::map_coordinate::MapCoordinate;
+    use crate::region::map_region::Rect;
+    use crate::tool::tile_mapper::{ContentQuantity, ResourceMap};
+
+    /// The side of the buckets used by `SpatialIndex::new`, in tiles.
+    pub const DEFAULT_CELL_SIZE: usize = 8;
+
+    /// Below this number of tiles a kind of content is scanned linearly, which is faster than
+    /// visiting the buckets.
+    const LINEAR_SCAN_LIMIT: usize = 32;
+
+    type Entry = (MapCoordinate, ContentQuantity);
+
+    /// Sorts entries by distance from `origin`, ties broken in reading order.
+    fn by_distance(origin: &MapCoordinate) -> impl Fn(&&Entry, &&Entry) -> std::cmp::Ordering + '_ {
+        move |a: &&Entry, b: &&Entry| {
+            a.0.get_distance(origin)
+                .total_cmp(&b.0.get_distance(origin))
+                .then(a.0.cmp(&b.0))
+        }
+    }
+
+    /// The tiles of one kind of content, grouped in square buckets.
+    #[derive(Debug, Clone, Default)]
+    struct Buckets {
+        cells: HashMap<(usize, usize), Vec<Entry>>,
+        len: usize,
+        // the range of the occupied cells, used to know when a search can stop
+        min_cell: (usize, usize),
+        max_cell: (usize, usize),
+    }
+
+    /// The `SpatialIndex` struct groups the tiles of every kind of content in square buckets,
+    /// so that nearest-neighbour and range queries only visit the buckets around the origin
+    /// instead of every tile.
+    ///
+    /// Distances are Euclidean, as in `MapCoordinate::get_distance`; tiles at the same distance
+    /// are returned in reading order.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::spatial::spatial_index::SpatialIndex;
+    ///
+    /// let spatial = SpatialIndex::from_resources(&TileMapper::collection(world).unwrap());
+    ///
+    /// let closest = spatial.nearest(&Content::Coin(0), &robot_position);
+    /// let three_closest = spatial.k_nearest(&Content::Coin(0), &robot_position, 3);
+    /// let around = spatial.in_range(&Content::Coin(0), &robot_position, 5.);
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct SpatialIndex {
+        cell_size: usize,
+        kinds: HashMap<Discriminant<Content>, Buckets>,
+    }
+
+    impl Default for SpatialIndex {
+        fn default() -> Self {
+            SpatialIndex::new()
+        }
+    }
+
+    impl SpatialIndex {
+        /// Creates an empty `SpatialIndex` with buckets of `DEFAULT_CELL_SIZE` tiles.
+        pub fn new() -> Self {
+            SpatialIndex::with_cell_size(DEFAULT_CELL_SIZE)
+        }
+
+        /// Creates an empty `SpatialIndex` with buckets of `cell_size` tiles (at least 1).
+        pub fn with_cell_size(cell_size: usize) -> Self {
+            SpatialIndex {
+                cell_size: cell_size.max(1),
+                kinds: HashMap::new(),
+            }
+        }
+
+        /// Creates a `SpatialIndex` holding every tile of `resources`.
+        pub fn from_resources(resources: &ResourceMap) -> Self {
+            let mut index = SpatialIndex::new();
+            for (kind, tiles) in resources.iter() {
+                for (coordinate, quantity) in tiles.iter() {
+                    index.insert_kind(*kind, *coordinate, quantity.clone());
+                }
+            }
+            index
+        }
+
+        /// Gets the side of the buckets, in tiles.
+        pub fn cell_size(&self) -> usize {
+            self.cell_size
+        }
+
+        /// Gets the number of tiles holding the same kind of `content`.
+        pub fn len(&self, content: &Content) -> usize {
+            self.kinds
+                .get(&discriminant(content))
+                .map(|buckets| buckets.len)
+                .unwrap_or(0)
+        }
+
+        /// Checks whether the index holds no tile at all.
+        pub fn is_empty(&self) -> bool {
+            self.kinds.is_empty()
+        }
+
+        /// Adds a tile holding `content` with the given quantity.
+        pub fn insert(
+            &mut self,
+            content: &Content,
+            coordinate: MapCoordinate,
+            quantity: ContentQuantity,
+        ) {
+            self.insert_kind(discriminant(content), coordinate, quantity);
+        }
+
+        fn insert_kind(
+            &mut self,
+            kind: Discriminant<Content>,
+            coordinate: MapCoordinate,
+            quantity: ContentQuantity,
+        ) {
+            let cell = self.cell_of(&coordinate);
+            let buckets = self.kinds.entry(kind).or_default();
+            if buckets.len == 0 {
+                buckets.min_cell = cell;
+                buckets.max_cell = cell;
+            } else {
+                buckets.min_cell = (
+                    buckets.min_cell.0.min(cell.0),
+                    buckets.min_cell.1.min(cell.1),
+                );
+                buckets.max_cell = (
+                    buckets.max_cell.0.max(cell.0),
+                    buckets.max_cell.1.max(cell.1),
+                );
+            }
+            buckets
+                .cells
+                .entry(cell)
+                .or_default()
+                .push((coordinate, quantity));
+            buckets.len += 1;
+        }
+
+        /// Removes the tile at `coordinate` holding the same kind of `content`, returning its quantity.
+        pub fn remove(
+            &mut self,
+            content: &Content,
+            coordinate: &MapCoordinate,
+        ) -> Option<ContentQuantity> {
+            let kind = discriminant(content);
+            let cell = self.cell_of(coordinate);
+            let buckets = self.kinds.get_mut(&kind)?;
+            let entries = buckets.cells.get_mut(&cell)?;
+            let position = entries.iter().position(|(c, _)| c == coordinate)?;
+            let (_, quantity) = entries.remove(position);
+            if entries.is_empty() {
+                buckets.cells.remove(&cell);
+            }
+            buckets.len -= 1;
+            if buckets.len == 0 {
+                self.kinds.remove(&kind);
+            }
+            // the occupied range is left as it is: it may only be larger than needed
+            Some(quantity)
+        }
+
+        /// Finds the tile holding the same kind of `content` closest to `origin`.
+        ///
+        /// Returns `None` if no tile holds it.
+        pub fn nearest(
+            &self,
+            content: &Content,
+            origin: &MapCoordinate,
+        ) -> Option<&(MapCoordinate, ContentQuantity)> {
+            self.k_nearest(content, origin, 1).into_iter().next()
+        }
+
+        /// Finds the `k` tiles holding the same kind of `content` closest to `origin`,
+        /// from the closest to the farthest.
+        pub fn k_nearest(
+            &self,
+            content: &Content,
+            origin: &MapCoordinate,
+            k: usize,
+        ) -> Vec<&(MapCoordinate, ContentQuantity)> {
+            let buckets = match self.kinds.get(&discriminant(content)) {
+                Some(buckets) if k > 0 => buckets,
+                _ => return vec![],
+            };
+
+            let mut found: Vec<&Entry> = vec![];
+            if buckets.len <= LINEAR_SCAN_LIMIT {
+                found = buckets.cells.values().flatten().collect();
+            } else {
+                let (cw, ch) = self.cell_of(origin);
+                // rings of cells are visited until the farthest occupied cell
+                let last_ring = [
+                    cw.abs_diff(buckets.min_cell.0),
+                    cw.abs_diff(buckets.max_cell.0),
+                    ch.abs_diff(buckets.min_cell.1),
+                    ch.abs_diff(buckets.max_cell.1),
+                ]
+                .into_iter()
+                .max()
+                .unwrap_or(0);
+                for ring in 0..=last_ring {
+                    for cell in ring_cells((cw, ch), ring) {
+                        if let Some(entries) = buckets.cells.get(&cell) {
+                            found.extend(entries.iter());
+                        }
+                    }
+                    // tiles in the next rings are more than `ring * cell_size` tiles away
+                    if found.len() >= k {
+                        found.sort_by(by_distance(origin));
+                        found.truncate(k);
+                        if found[k - 1].0.get_distance(origin) <= (ring * self.cell_size) as f64 {
+                            break;
+                        }
+                    }
+                }
+            }
+            found.sort_by(by_distance(origin));
+            found.truncate(k);
+            found
+        }
+
+        /// Finds every tile holding the same kind of `content` at most `radius` tiles away
+        /// from `origin`, from the closest to the farthest.
+        pub fn in_range(
+            &self,
+            content: &Content,
+            origin: &MapCoordinate,
+            radius: f64,
+        ) -> Vec<&(MapCoordinate, ContentQuantity)> {
+            if radius < 0. {
+                return vec![];
+            }
+            let reach = radius.floor() as usize;
+            let region = Rect::new(
+                MapCoordinate::new(
+                    origin.get_width().saturating_sub(reach),
+                    origin.get_height().saturating_sub(reach),
+                ),
+                MapCoordinate::new(
+                    origin.get_width().saturating_add(reach),
+                    origin.get_height().saturating_add(reach),
+                ),
+            );
+            let mut found: Vec<&Entry> = self
+                .in_rect(content, &region)
+                .into_iter()
+                .filter(|(c, _)| c.get_distance(origin) <= radius)
+                .collect();
+            found.sort_by(by_distance(origin));
+            found
+        }
+
+        /// Finds every tile holding the same kind of `content` inside `region`, in reading order.
+        pub fn in_rect(
+            &self,
+            content: &Content,
+            region: &Rect,
+        ) -> Vec<&(MapCoordinate, ContentQuantity)> {
+            let buckets = match self.kinds.get(&discriminant(content)) {
+                Some(buckets) => buckets,
+                None => return vec![],
+            };
+            let (first, last) = (
+                self.cell_of(&region.top_left()),
+                self.cell_of(&region.bottom_right()),
+            );
+            // visit the smaller of the cells covering the region and the occupied cells
+            let mut found: Vec<&Entry> = if (last.0 - first.0 + 1)
+                .saturating_mul(last.1 - first.1 + 1)
+                < buckets.cells.len()
+            {
+                (first.1..=last.1)
+                    .flat_map(|row| (first.0..=last.0).map(move |column| (column, row)))
+                    .filter_map(|cell| buckets.cells.get(&cell))
+                    .flatten()
+                    .collect()
+            } else {
+                buckets.cells.values().flatten().collect()
+            };
+            found.retain(|(c, _)| region.contains(c));
+            found.sort_by(|a, b| a.0.cmp(&b.0));
+            found
+        }
+
+        fn cell_of(&self, coordinate: &MapCoordinate) -> (usize, usize) {
+            (
+                coordinate.get_width() / self.cell_size,
+                coordinate.get_height() / self.cell_size,
+            )
+        }
+    }
+
+    /// Returns the cells exactly `ring` cells away from `center`, diagonal steps included.
+    fn ring_cells(center: (usize, usize), ring: usize) -> Vec<(usize, usize)> {
+        if ring == 0 {
+            return vec![center];
+        }
+        let (cw, ch) = (center.0 as isize, center.1 as isize);
+        let r = ring as isize;
+        let mut cells = vec![];
+        for dw in -r..=r {
+            cells.push((cw + dw, ch - r));
+            cells.push((cw + dw, ch + r));
+        }
+        for dh in (-r + 1)..r {
+            cells.push((cw - r, ch + dh));
+            cells.push((cw + r, ch + dh));
+        }
+        cells
+            .into_iter()
+            .filter(|(w, h)| *w >= 0 && *h >= 0)
+            .map(|(w, h)| (w as usize, h as usize))
+            .collect()
+    }
+}
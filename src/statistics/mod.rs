@@ -0,0 +1,221 @@
+pub mod resource_statistics {
+    use std::collections::HashMap;
+    use std::fmt::{Display, Formatter};
+    use std::mem::{discriminant, Discriminant};
+
+    use robotics_lib::world::tile::{Content, Tile};
+
+    use crate::coordinates::map_coordinate::MapCoordinate;
+    use crate::source::map_source::MapSource;
+    use crate::tool::tile_mapper::{
+        collect_resources, kind_name, stored_amount, ContentQuantity, ResourceMap, TileMapper,
+    };
+
+    /// Aggregate figures about every discovered tile holding the same kind of `Content`.
+    ///
+    /// ## Fields
+    ///
+    /// - `tiles`: The number of tiles holding the content.
+    /// - `total_quantity`: The sum of the amounts held by the tiles.
+    /// - `min_quantity`, `max_quantity`, `mean_quantity`: The amount held by a single tile.
+    /// - `total_capacity`: The sum of the maximum capacities, only meaningful for `Bin`, `Crate` and `Bank`.
+    /// - `centroid`: The mean `(width, height)` of the tiles.
+    /// - `bounding_box`: The top-left and bottom-right corners of the smallest rectangle containing every tile.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ContentStatistics {
+        pub tiles: usize,
+        pub total_quantity: usize,
+        pub min_quantity: usize,
+        pub max_quantity: usize,
+        pub mean_quantity: f64,
+        pub total_capacity: usize,
+        pub centroid: (f64, f64),
+        pub bounding_box: (MapCoordinate, MapCoordinate),
+    }
+
+    impl ContentStatistics {
+        /// Computes the statistics of a non-empty list of tiles, as stored in a `ResourceMap`.
+        fn from_tiles(tiles: &[(MapCoordinate, ContentQuantity)]) -> Option<Self> {
+            let (first, _) = tiles.first()?;
+            let mut statistics = ContentStatistics {
+                tiles: tiles.len(),
+                total_quantity: 0,
+                min_quantity: usize::MAX,
+                max_quantity: 0,
+                mean_quantity: 0.,
+                total_capacity: 0,
+                centroid: (0., 0.),
+                bounding_box: (*first, *first),
+            };
+            let (mut top_left, mut bottom_right) = statistics.bounding_box;
+
+            for (coordinate, quantity) in tiles.iter() {
+                let amount = stored_amount(quantity);
+                statistics.total_quantity += amount;
+                statistics.min_quantity = statistics.min_quantity.min(amount);
+                statistics.max_quantity = statistics.max_quantity.max(amount);
+                if let (None, Some(range)) = quantity {
+                    statistics.total_capacity += range.end;
+                }
+
+                statistics.centroid.0 += coordinate.get_width() as f64;
+                statistics.centroid.1 += coordinate.get_height() as f64;
+                top_left.set_width(top_left.get_width().min(coordinate.get_width()));
+                top_left.set_height(top_left.get_height().min(coordinate.get_height()));
+                bottom_right.set_width(bottom_right.get_width().max(coordinate.get_width()));
+                bottom_right.set_height(bottom_right.get_height().max(coordinate.get_height()));
+            }
+
+            let count = tiles.len() as f64;
+            statistics.mean_quantity = statistics.total_quantity as f64 / count;
+            statistics.centroid = (statistics.centroid.0 / count, statistics.centroid.1 / count);
+            statistics.bounding_box = (top_left, bottom_right);
+            Some(statistics)
+        }
+    }
+
+    /// The `ResourceReport` struct collects the statistics of every discovered kind of `Content`,
+    /// together with how much of the world has been discovered.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+    ///
+    /// if let Some(report) = TileMapper::statistics(world) {
+    ///     println!("{}", report);
+    ///     if let Some(rocks) = report.get(&Content::Rock(0)) {
+    ///         println!("{} rocks on {} tiles", rocks.total_quantity, rocks.tiles);
+    ///     }
+    /// }
+    /// ```
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ResourceReport {
+        contents: HashMap<Discriminant<Content>, ContentStatistics>,
+        discovered_tiles: usize,
+        world_tiles: usize,
+    }
+
+    impl ResourceReport {
+        /// Computes the report of a map such as the one returned by `robot_map`.
+        pub fn from_map(robot_world: &[Vec<Option<Tile>>]) -> Self {
+            let resources = collect_resources(robot_world);
+            let mut report = ResourceReport::from_resources(&resources);
+            report.world_tiles = robot_world.iter().map(|row| row.len()).sum();
+            report.discovered_tiles = robot_world
+                .iter()
+                .flat_map(|row| row.iter())
+                .filter(|tile| tile.is_some())
+                .count();
+            report
+        }
+
+        /// Computes the report of an already built resource map.
+        ///
+        /// The map does not tell how big the world is, so the discovered fraction is left at 0.
+        pub fn from_resources(resources: &ResourceMap) -> Self {
+            let contents = resources
+                .iter()
+                .filter_map(|(kind, tiles)| {
+                    ContentStatistics::from_tiles(tiles).map(|statistics| (*kind, statistics))
+                })
+                .collect();
+            ResourceReport {
+                contents,
+                discovered_tiles: 0,
+                world_tiles: 0,
+            }
+        }
+
+        /// Gets the statistics of the same kind of `content`, if it has been discovered.
+        pub fn get(&self, content: &Content) -> Option<&ContentStatistics> {
+            self.contents.get(&discriminant(content))
+        }
+
+        /// Gets the statistics of every discovered kind of `Content`.
+        pub fn contents(&self) -> &HashMap<Discriminant<Content>, ContentStatistics> {
+            &self.contents
+        }
+
+        /// Gets the number of discovered tiles.
+        pub fn discovered_tiles(&self) -> usize {
+            self.discovered_tiles
+        }
+
+        /// Gets the number of tiles of the world.
+        pub fn world_tiles(&self) -> usize {
+            self.world_tiles
+        }
+
+        /// Gets the fraction of the world discovered so far, between 0 and 1.
+        pub fn discovered_fraction(&self) -> f64 {
+            if self.world_tiles == 0 {
+                return 0.;
+            }
+            self.discovered_tiles as f64 / self.world_tiles as f64
+        }
+    }
+
+    impl Display for ResourceReport {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            writeln!(
+                f,
+                "Discovered {} of {} tiles ({:.1}%)",
+                self.discovered_tiles,
+                self.world_tiles,
+                self.discovered_fraction() * 100.
+            )?;
+            let mut kinds: Vec<&Discriminant<Content>> = self.contents.keys().collect();
+            kinds.sort_by_key(|kind| kind_name(kind));
+            for kind in kinds {
+                let s = &self.contents[kind];
+                writeln!(
+                    f,
+                    "{}: {} on {} tiles (min {}, max {}, mean {:.2}), centroid ({:.1}, {:.1}), box {} - {}",
+                    kind_name(kind),
+                    s.total_quantity,
+                    s.tiles,
+                    s.min_quantity,
+                    s.max_quantity,
+                    s.mean_quantity,
+                    s.centroid.0,
+                    s.centroid.1,
+                    s.bounding_box.0,
+                    s.bounding_box.1
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    impl TileMapper {
+        /// Computes aggregate statistics over every tile discovered by the robot.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the `World`.
+        ///
+        /// # Returns
+        ///
+        /// Returns `Some(ResourceReport)` if the world has been discovered, `None` otherwise.
+        /// Sources without a tile grid, such as a replayed map, give a report whose discovered fraction is 0.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
+        ///
+        /// match TileMapper::statistics(world) {
+        ///     Some(report) => println!("{:.0}% discovered", report.discovered_fraction() * 100.),
+        ///     None => // handle case where the robot has not discovered anything yet
+        /// }
+        /// ```
+        pub fn statistics<S: MapSource + ?Sized>(source: &S) -> Option<ResourceReport> {
+            match source.tiles() {
+                Some(robot_world) => Some(ResourceReport::from_map(&robot_world)),
+                None => source
+                    .resources()
+                    .map(|resources| ResourceReport::from_resources(&resources)),
+            }
+        }
+    }
+}
@@ -1,23 +1,49 @@
 #[cfg(test)]
 mod tests {
-    use crate::coordinates::map_coordinate::MapCoordinate;
-    use crate::tool::tile_mapper::{ContentQuantity, TileMapper};
+    use crate::clustering::deposits::ClusterMode;
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate, Offset};
+    use crate::cost::cost_model::CostModel;
+    use crate::depletion::depletion_tracker::DepletionTracker;
+    use crate::diff::resource_diff::{DiffEntry, ResourceDiff};
+    use crate::discovery::discovery_mask::DiscoveryMask;
+    use crate::errors::tool_errors::ToolError;
+    use crate::executor::plan_executor::{ExecutionStatus, PlanExecutor, MAX_ENERGY_LEVEL};
+    use crate::fixtures::test_maps::test_map;
+    use crate::goals::goal_solver::{Action, Constraints, EndLocation, Goal};
+    use crate::history::resource_history::{HistoryRecord, HistoryRecorder, HistoryReplayer};
+    use crate::index::resource_index::ResourceIndex;
+    use crate::observer::resource_observer::ResourceFilter;
+    use crate::path::path_finder::{
+        find_teleports, PathFinder, PathOptions, PathStep, UnknownTiles,
+    };
+    use crate::prediction::resource_prediction::{Lead, PredictionModel};
+    use crate::region::map_region::Rect;
+    use crate::scoring::target_scoring::ScoringWeights;
+    use crate::source::map_source::MapSource;
+    use crate::spatial::spatial_index::SpatialIndex;
+    use crate::statistics::resource_statistics::ResourceReport;
+    use crate::tool::tile_mapper::{
+        grid_entries, map_entries, ContainerQuery, ContentQuantity, TileMapper,
+    };
     use robotics_lib::energy::Energy;
     use robotics_lib::event::events::Event;
-    use robotics_lib::interface::{go, Direction};
+    use robotics_lib::interface::{destroy, discover_tiles, go, put, Direction};
     use robotics_lib::runner::backpack::BackPack;
     use robotics_lib::runner::{Robot, Runnable, Runner};
+    use robotics_lib::utils::LibError;
     use robotics_lib::world::coordinates::Coordinate;
     use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
-    use robotics_lib::world::environmental_conditions::WeatherType::Sunny;
-    use robotics_lib::world::tile::Content::{Bin, Coin, Rock};
+    use robotics_lib::world::environmental_conditions::WeatherType::{Rainy, Sunny};
+    use robotics_lib::world::tile::Content::{Bank, Bin, Coin, Garbage, Market, Rock, Tree};
     use robotics_lib::world::tile::{Content, Tile, TileType};
     use robotics_lib::world::world_generator::Generator;
     use robotics_lib::world::world_generator::World as WorldType;
     use robotics_lib::world::World;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
+    use std::error::Error;
     use std::mem;
     use std::mem::Discriminant;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_new_map_coordinate() {
@@ -832,7 +858,7 @@ mod tests {
                     go(self, world, dir.to_owned()).expect("");
                 }
 
-                let expected_error = "Content not discovered yet".to_string();
+                let expected_error = "Bush not discovered yet".to_string();
 
                 match tool.find_most_loaded(world, self, Content::Bush(0)) {
                     Ok(_result) => {}
@@ -1226,6 +1252,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_find_closest2() {
         struct TestRobot(Robot);
         impl Runnable for TestRobot {
@@ -1365,4 +1392,2242 @@ mod tests {
         );
         let _ = runner.unwrap().game_tick();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_diff_added_removed_changed() {
+        let mut before: HashMap<Discriminant<Content>, Vec<(MapCoordinate, ContentQuantity)>> =
+            HashMap::new();
+        before.insert(
+            mem::discriminant(&Rock(0)),
+            vec![
+                (MapCoordinate::new(3, 1), (Some(17), None)),
+                (MapCoordinate::new(2, 2), (Some(2), None)),
+            ],
+        );
+        before.insert(
+            mem::discriminant(&Coin(0)),
+            vec![(MapCoordinate::new(2, 1), (Some(3), None))],
+        );
+
+        let mut after: HashMap<Discriminant<Content>, Vec<(MapCoordinate, ContentQuantity)>> =
+            HashMap::new();
+        after.insert(
+            mem::discriminant(&Rock(0)),
+            vec![
+                (MapCoordinate::new(3, 1), (Some(10), None)),
+                (MapCoordinate::new(4, 4), (Some(1), None)),
+            ],
+        );
+        after.insert(
+            mem::discriminant(&Bin(0..0)),
+            vec![(MapCoordinate::new(1, 1), (None, Some(0..4)))],
+        );
+
+        let diff = TileMapper::diff(&before, &after);
+        assert_eq!(diff.len(), 5);
+        assert_eq!(diff.added().count(), 2);
+        assert_eq!(diff.removed().count(), 2);
+        assert_eq!(diff.changed().count(), 1);
+        assert_eq!(
+            diff.changed().next(),
+            Some(&DiffEntry::Changed {
+                kind: mem::discriminant(&Rock(0)),
+                coordinate: MapCoordinate::new(3, 1),
+                before: (Some(17), None),
+                after: (Some(10), None),
+            })
+        );
+        assert_eq!(diff.for_content(&Coin(1)).count(), 1);
+        assert_eq!(
+            diff.summary(),
+            "Bin: 1 added, 0 removed, 0 changed\n\
+             Coin: 0 added, 1 removed, 0 changed\n\
+             Rock: 1 added, 1 removed, 1 changed"
+        );
+
+        // applying the diff turns the old map into the new one
+        let mut applied = before.clone();
+        diff.apply_to(&mut applied);
+        assert_eq!(applied, after);
+
+        // entries touching the same tile, as in a replayed history, are applied in order
+        let rock = mem::discriminant(&Rock(0));
+        let coordinate = MapCoordinate::new(0, 0);
+        let history = ResourceDiff::from_entries(vec![
+            DiffEntry::Added {
+                kind: rock,
+                coordinate,
+                quantity: (Some(1), None),
+            },
+            DiffEntry::Changed {
+                kind: rock,
+                coordinate,
+                before: (Some(1), None),
+                after: (Some(5), None),
+            },
+            DiffEntry::Removed {
+                kind: rock,
+                coordinate: MapCoordinate::new(4, 4),
+                quantity: (Some(1), None),
+            },
+            DiffEntry::Changed {
+                kind: rock,
+                coordinate: MapCoordinate::new(4, 4),
+                before: (Some(1), None),
+                after: (Some(2), None),
+            },
+        ]);
+        history.apply_to(&mut applied);
+        assert_eq!(
+            applied[&rock],
+            vec![
+                (coordinate, (Some(5), None)),
+                (MapCoordinate::new(3, 1), (Some(10), None))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical_maps() {
+        let mut map: HashMap<Discriminant<Content>, Vec<(MapCoordinate, ContentQuantity)>> =
+            HashMap::new();
+        map.insert(
+            mem::discriminant(&Rock(0)),
+            vec![(MapCoordinate::new(3, 1), (Some(17), None))],
+        );
+
+        let diff = TileMapper::diff(&map, &map.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.summary(), "No changes");
+    }
+
+    #[test]
+    fn test_history_record_parsing() {
+        let record = HistoryRecord {
+            tick: 200,
+            entry: DiffEntry::Changed {
+                kind: mem::discriminant(&Bin(0..0)),
+                coordinate: MapCoordinate::new(1, 1),
+                before: (None, Some(0..4)),
+                after: (None, Some(2..4)),
+            },
+        };
+        assert_eq!(record.to_string(), "200 ~ Bin 1,1 0..4 2..4");
+        assert_eq!(record.to_string().parse::<HistoryRecord>().unwrap(), record);
+        assert!("200 ? Bin 1,1 0..4".parse::<HistoryRecord>().is_err());
+        assert!("200 + Dragon 1,1 3".parse::<HistoryRecord>().is_err());
+    }
+
+    #[test]
+    fn test_history_record_and_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "tile_resource_mapper_history_{}.txt",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let first = test_map(5, &[(3, 1, Rock(17)), (2, 1, Coin(3))]);
+        let second = test_map(5, &[(3, 1, Rock(10)), (1, 1, Bin(0..4))]);
+        {
+            let recorder = HistoryRecorder::create(&path).unwrap();
+            let mut index = ResourceIndex::with_recorder(recorder);
+            let changes = index.update_from_map(first.clone(), 100).unwrap();
+            assert_eq!(changes.added().count(), 2);
+            let changes = index.update_from_map(second.clone(), 200).unwrap();
+            assert_eq!(changes.len(), 3);
+            assert_eq!(index.tick(), 200);
+        }
+
+        let mut replayer = HistoryReplayer::open(&path).unwrap();
+        assert_eq!(replayer.ticks(), vec![100, 200]);
+        assert_eq!(Some(replayer.state_at(150)), TileMapper::collection(&first));
+        assert_eq!(
+            Some(replayer.state_at(200)),
+            TileMapper::collection(&second)
+        );
+        assert!(replayer.state_at(50).is_empty());
+
+        assert_eq!(replayer.step().map(|(tick, _)| tick), Some(100));
+        let (tick, changes) = replayer.step().unwrap();
+        assert_eq!(tick, 200);
+        assert_eq!(changes.changed().count(), 1);
+        assert_eq!(
+            Some(replayer.state().clone()),
+            TileMapper::collection(&second)
+        );
+        assert!(replayer.step().is_none());
+
+        replayer.rewind();
+        assert_eq!(replayer.tick(), None);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let mut map = test_map(
+            5,
+            &[
+                (3, 1, Rock(17)),
+                (2, 2, Rock(2)),
+                (4, 4, Rock(5)),
+                (1, 1, Bin(1..4)),
+                (0, 3, Bin(0..6)),
+            ],
+        );
+        // leave two tiles undiscovered
+        map[0][0] = None;
+        map[0][1] = None;
+
+        let report = ResourceReport::from_map(&map);
+        assert_eq!(report.world_tiles(), 25);
+        assert_eq!(report.discovered_tiles(), 23);
+        assert_eq!(report.discovered_fraction(), 0.92);
+
+        let rocks = report.get(&Rock(0)).unwrap();
+        assert_eq!(rocks.tiles, 3);
+        assert_eq!(rocks.total_quantity, 24);
+        assert_eq!(rocks.min_quantity, 2);
+        assert_eq!(rocks.max_quantity, 17);
+        assert_eq!(rocks.mean_quantity, 8.);
+        assert_eq!(rocks.centroid, (3., 7. / 3.));
+        assert_eq!(
+            rocks.bounding_box,
+            (MapCoordinate::new(2, 1), MapCoordinate::new(4, 4))
+        );
+
+        let bins = report.get(&Bin(0..0)).unwrap();
+        assert_eq!(bins.total_quantity, 1);
+        assert_eq!(bins.total_capacity, 10);
+        assert!(report.get(&Coin(0)).is_none());
+    }
+
+    #[test]
+    fn test_find_deposits_connected() {
+        let map = test_map(
+            6,
+            &[
+                (0, 0, Rock(1)),
+                (1, 0, Rock(2)),
+                (1, 1, Rock(3)),
+                (4, 4, Rock(5)),
+                (5, 5, Rock(2)),
+            ],
+        );
+        let resources = TileMapper::collection(&map).unwrap();
+        let origin = MapCoordinate::new(5, 0);
+
+        let deposits = TileMapper::find_deposits(
+            &resources,
+            &Rock(0),
+            ClusterMode::Connected { diagonal: false },
+            origin,
+        );
+        assert_eq!(deposits.len(), 3);
+        assert_eq!(deposits[0].total_quantity, 6);
+        assert_eq!(deposits[0].size(), 3);
+        assert_eq!(deposits[0].entry_point, MapCoordinate::new(1, 0));
+        assert_eq!(deposits[0].centroid, (2. / 3., 1. / 3.));
+        assert_eq!(deposits[1].total_quantity, 5);
+
+        let deposits = TileMapper::find_deposits(
+            &resources,
+            &Rock(0),
+            ClusterMode::Connected { diagonal: true },
+            origin,
+        );
+        assert_eq!(deposits.len(), 2);
+        assert_eq!(deposits[0].total_quantity, 7);
+        assert_eq!(deposits[0].entry_point, MapCoordinate::new(4, 4));
+
+        assert!(
+            TileMapper::find_deposits(&resources, &Tree(0), ClusterMode::default(), origin)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_find_deposits_radius() {
+        let map = test_map(
+            8,
+            &[
+                (0, 0, Tree(1)),
+                (2, 0, Tree(1)),
+                (4, 0, Tree(1)),
+                (7, 7, Tree(9)),
+            ],
+        );
+        let resources = TileMapper::collection(&map).unwrap();
+        let origin = MapCoordinate::new(0, 0);
+
+        let deposits = TileMapper::find_deposits(
+            &resources,
+            &Tree(0),
+            ClusterMode::Radius {
+                radius: 2.,
+                min_tiles: 2,
+            },
+            origin,
+        );
+        assert_eq!(deposits.len(), 2);
+        assert_eq!(deposits[0].total_quantity, 9);
+        assert_eq!(deposits[1].size(), 3);
+        assert_eq!(deposits[1].entry_point, origin);
+
+        // no tile has enough neighbours to be a core tile
+        let deposits = TileMapper::find_deposits(
+            &resources,
+            &Tree(0),
+            ClusterMode::Radius {
+                radius: 2.,
+                min_tiles: 4,
+            },
+            origin,
+        );
+        assert_eq!(deposits.len(), 4);
+    }
+
+    #[test]
+    fn test_score_targets() {
+        let map = test_map(10, &[(1, 0, Rock(2)), (9, 9, Rock(3)), (2, 0, Rock(1))]);
+        let resources = TileMapper::collection(&map).unwrap();
+        let origin = MapCoordinate::new(0, 0);
+
+        let targets =
+            TileMapper::score_targets(&resources, &Rock(0), origin, &ScoringWeights::default());
+        assert_eq!(targets.len(), 3);
+        // the nearby tile wins over the richer one on the other side of the map
+        assert_eq!(targets[0].coordinate, MapCoordinate::new(1, 0));
+        assert_eq!(targets[0].travel_cost, 2.);
+        assert_eq!(targets[0].score, 1.);
+        assert_eq!(targets[2].coordinate, MapCoordinate::new(9, 9));
+
+        let weights = ScoringWeights {
+            neighbourhood_radius: 1.,
+            neighbourhood_weight: 7.,
+            ..ScoringWeights::default()
+        };
+        let targets = TileMapper::score_targets(&resources, &Rock(0), origin, &weights);
+        // (1 + 2 * 7) / 3 is better than (2 + 1 * 7) / 2
+        assert_eq!(targets[0].coordinate, MapCoordinate::new(2, 0));
+        assert_eq!(targets[0].neighbourhood_quantity, 2);
+        assert_eq!(targets[0].score, 5.);
+
+        assert!(TileMapper::score_targets(&resources, &Coin(0), origin, &weights).is_empty());
+    }
+
+    #[test]
+    fn test_container_query() {
+        assert_eq!(ContainerQuery::LargestCapacity.value(&(2..10)), 10);
+        assert_eq!(ContainerQuery::MostFreeCapacity.value(&(2..10)), 8);
+        assert_eq!(ContainerQuery::Fullest.value(&(2..10)), 2);
+        assert_eq!(ContainerQuery::MostFreeCapacity.value(&(0..0)), 0);
+    }
+
+    #[test]
+    fn test_find_most_loaded_containers() {
+        // the closest bin can accept more, the farthest one holds more
+        let map = test_map(5, &[(1, 0, Bin(0..9)), (4, 4, Bin(5..6))]);
+        let mapper = TileMapper {};
+        let origin = MapCoordinate::new(0, 0);
+        assert_eq!(
+            mapper.find_most_loaded(&map, &origin, Bin(0..0)).unwrap(),
+            MapCoordinate::new(4, 4)
+        );
+        assert_eq!(
+            mapper
+                .find_container(&map, &origin, Bin(0..0), ContainerQuery::MostFreeCapacity)
+                .unwrap(),
+            MapCoordinate::new(1, 0)
+        );
+    }
+
+    #[test]
+    fn test_find_container() {
+        struct TestRobot(Robot);
+        impl Runnable for TestRobot {
+            fn process_tick(&mut self, world: &mut World) {
+                let tool = TileMapper {};
+                // path the robot must follow
+                let directions = [
+                    Direction::Right,
+                    Direction::Down,
+                    Direction::Right,
+                    Direction::Down,
+                    Direction::Up,
+                    Direction::Right,
+                    Direction::Up,
+                    Direction::Right,
+                ];
+
+                //move the robot
+                for (_phase, dir) in directions.iter().enumerate() {
+                    go(self, world, dir.to_owned()).expect("");
+                }
+
+                let expected = [
+                    (ContainerQuery::LargestCapacity, MapCoordinate::new(3, 1)),
+                    (ContainerQuery::MostFreeCapacity, MapCoordinate::new(2, 1)),
+                    (ContainerQuery::Fullest, MapCoordinate::new(1, 1)),
+                ];
+                for (query, expected_result) in expected {
+                    match tool.find_container(world, self, Content::Bin(0..0), query) {
+                        Ok(result) => {
+                            assert_eq!(result, expected_result);
+                        }
+                        Err(e) => panic!("{}", e),
+                    };
+                }
+
+                // an empty range can still be chosen
+                match tool.find_most_loaded(world, self, Content::Crate(0..0)) {
+                    Ok(result) => {
+                        assert_eq!(result, MapCoordinate::new(2, 2));
+                    }
+                    Err(e) => panic!("{}", e),
+                };
+
+                match tool.find_container(world, self, Content::Rock(0), ContainerQuery::Fullest) {
+                    Ok(_result) => panic!("a rock is not a container"),
+                    Err(e) => assert_eq!(e.to_string(), "Rock is not a container"),
+                };
+            }
+            fn handle_event(&mut self, event: Event) {
+                println!();
+                println!("{:?}", event);
+                println!();
+            }
+            fn get_energy(&self) -> &Energy {
+                &self.0.energy
+            }
+            fn get_energy_mut(&mut self) -> &mut Energy {
+                &mut self.0.energy
+            }
+            fn get_coordinate(&self) -> &Coordinate {
+                &self.0.coordinate
+            }
+            fn get_coordinate_mut(&mut self) -> &mut Coordinate {
+                &mut self.0.coordinate
+            }
+            fn get_backpack(&self) -> &BackPack {
+                &self.0.backpack
+            }
+            fn get_backpack_mut(&mut self) -> &mut BackPack {
+                &mut self.0.backpack
+            }
+        }
+
+        struct WorldGenerator {
+            size: usize,
+            spawn_x: usize,
+            spawn_y: usize,
+            tile_type: TileType,
+        }
+
+        impl WorldGenerator {
+            fn new(size: usize, spawn_x: usize, spawn_y: usize, tile_type: TileType) -> Self {
+                Self {
+                    size,
+                    spawn_x,
+                    spawn_y,
+                    tile_type,
+                }
+            }
+        }
+
+        impl Generator for WorldGenerator {
+            fn gen(&mut self) -> WorldType {
+                let mut map: Vec<Vec<Tile>> = Vec::new();
+                // Initialize the map with default tiles
+                for _ in 0..self.size {
+                    let mut row: Vec<Tile> = Vec::new();
+                    for _ in 0..self.size {
+                        let tile = Tile {
+                            tile_type: self.tile_type,
+                            content: Content::None,
+                            elevation: 0,
+                        };
+                        row.push(tile);
+                    }
+                    map.push(row);
+                }
+                // the biggest bin
+                map[1][3] = Tile {
+                    tile_type: self.tile_type,
+                    content: Content::Bin(6..10),
+                    elevation: 0,
+                };
+                // the emptiest bin
+                map[1][2] = Tile {
+                    tile_type: self.tile_type,
+                    content: Content::Bin(0..5),
+                    elevation: 0,
+                };
+                // the fullest bin
+                map[1][1] = Tile {
+                    tile_type: self.tile_type,
+                    content: Content::Bin(7..8),
+                    elevation: 0,
+                };
+                // a full crate
+                map[2][2] = Tile {
+                    tile_type: self.tile_type,
+                    content: Content::Crate(3..3),
+                    elevation: 0,
+                };
+
+                let environmental_conditions =
+                    EnvironmentalConditions::new(&vec![Sunny], 15, 12).unwrap();
+                // implementation
+                return (
+                    map,
+                    (self.spawn_x, self.spawn_y),
+                    environmental_conditions,
+                    10.0,
+                    None,
+                );
+            }
+        }
+
+        let r = TestRobot(Robot::new());
+        let runner = Runner::new(
+            Box::new(r),
+            &mut WorldGenerator::new(5, 0, 0, TileType::Grass),
+        );
+        let _ = runner.unwrap().game_tick();
+    }
+
+    #[test]
+    fn test_drop_off_destinations() {
+        let map = test_map(
+            10,
+            &[
+                (1, 0, Bin(3..4)),
+                (5, 0, Bin(0..10)),
+                (9, 9, Bin(4..4)),
+                (0, 2, Bank(0..100)),
+                (3, 3, Market(5)),
+            ],
+        );
+        let resources = TileMapper::collection(&map).unwrap();
+        let mut backpack = HashMap::new();
+        backpack.insert(Garbage(0), 3);
+        backpack.insert(Coin(0), 0);
+        backpack.insert(Tree(0), 2);
+
+        let destinations =
+            TileMapper::drop_off_destinations(&resources, &backpack, MapCoordinate::new(0, 0));
+        // the full bin is left out, the bank is useless without coins
+        assert_eq!(destinations.len(), 3);
+        // destinations accepting everything come first, even if the closest bin is nearer
+        assert_eq!(destinations[0].coordinate, MapCoordinate::new(3, 3));
+        assert_eq!(destinations[0].deliverable, 2);
+        assert_eq!(destinations[1].coordinate, MapCoordinate::new(5, 0));
+        assert_eq!(destinations[1].free_capacity, 10);
+        assert_eq!(destinations[2].coordinate, MapCoordinate::new(1, 0));
+        assert_eq!(destinations[2].deliverable, 1);
+    }
+
+    #[test]
+    fn test_plan_delivery() {
+        let map = test_map(
+            10,
+            &[
+                (9, 0, Garbage(4)),
+                (2, 2, Garbage(7)),
+                (0, 9, Bin(0..5)),
+                (3, 2, Bin(0..5)),
+            ],
+        );
+        let resources = TileMapper::collection(&map).unwrap();
+
+        let plan = TileMapper::plan_delivery(&resources, &Garbage(0), MapCoordinate::new(0, 0), 6)
+            .unwrap();
+        assert_eq!(plan.source, MapCoordinate::new(2, 2));
+        assert_eq!(plan.destination.coordinate, MapCoordinate::new(3, 2));
+        assert_eq!(plan.amount, 5);
+        assert_eq!(plan.total_distance, 8_f64.sqrt() + 1.);
+
+        assert!(
+            TileMapper::plan_delivery(&resources, &Garbage(0), MapCoordinate::new(0, 0), 0)
+                .is_none()
+        );
+        assert!(
+            TileMapper::plan_delivery(&resources, &Rock(0), MapCoordinate::new(0, 0), 6).is_none()
+        );
+    }
+
+    #[test]
+    fn test_path_finder() {
+        let mut map = test_map(5, &[]);
+        // a river crossing the map, except on the last row
+        for row in map.iter_mut().take(4) {
+            row[2] = Some(Tile {
+                tile_type: TileType::DeepWater,
+                content: Content::None,
+                elevation: 0,
+            });
+        }
+        let finder = PathFinder::new(&map, PathOptions::default());
+        let path = finder
+            .find(MapCoordinate::new(0, 0), MapCoordinate::new(4, 0))
+            .unwrap();
+        assert_eq!(path.len(), 12);
+        assert_eq!(path.end(), Some(MapCoordinate::new(4, 0)));
+        assert!(path.tiles.contains(&MapCoordinate::new(2, 4)));
+        assert_eq!(path.cost, 12 * TileType::Grass.properties().cost());
+        let directions = path.directions().unwrap();
+        let count = |d: Direction| directions.iter().filter(|x| **x == d).count();
+        assert_eq!(count(Direction::Down), 4);
+        assert_eq!(count(Direction::Right), 4);
+        assert_eq!(count(Direction::Up), 4);
+
+        // undiscovered tiles are blocked by default
+        map[4][2] = None;
+        let finder = PathFinder::new(&map, PathOptions::default());
+        assert!(finder
+            .find(MapCoordinate::new(0, 0), MapCoordinate::new(4, 0))
+            .is_none());
+
+        let finder = PathFinder::new(
+            &map,
+            PathOptions {
+                unknown_tiles: UnknownTiles::Cost(1),
+                ..PathOptions::default()
+            },
+        );
+        assert_eq!(
+            finder
+                .find(MapCoordinate::new(0, 0), MapCoordinate::new(4, 0))
+                .map(|path| path.len()),
+            Some(12)
+        );
+
+        // blocked tiles are never entered, even when they could be
+        let blocked = HashSet::from([MapCoordinate::new(2, 4)]);
+        let finder = PathFinder::new(
+            &map,
+            PathOptions {
+                unknown_tiles: UnknownTiles::Cost(1),
+                ..PathOptions::default()
+            },
+        )
+        .with_blocked(&blocked);
+        assert!(finder
+            .find(MapCoordinate::new(0, 0), MapCoordinate::new(4, 0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_path_finder_elevation_and_adjacent() {
+        let mut map = test_map(3, &[(2, 1, Rock(4))]);
+        // a steep hill in the middle of the map
+        if let Some(tile) = map[1][1].as_mut() {
+            tile.elevation = 10;
+        }
+        let finder = PathFinder::new(&map, PathOptions::default());
+        let path = finder
+            .find(MapCoordinate::new(0, 1), MapCoordinate::new(2, 1))
+            .unwrap();
+        // going around the hill is cheaper than climbing it
+        assert_eq!(path.len(), 4);
+        assert!(!path.tiles.contains(&MapCoordinate::new(1, 1)));
+
+        let (path, direction) = finder
+            .find_adjacent(MapCoordinate::new(0, 0), MapCoordinate::new(2, 1))
+            .unwrap();
+        assert_eq!(path.end(), Some(MapCoordinate::new(2, 0)));
+        assert_eq!(direction, Direction::Down);
+
+        let (path, direction) = finder
+            .find_adjacent(MapCoordinate::new(2, 2), MapCoordinate::new(2, 1))
+            .unwrap();
+        assert!(path.is_empty());
+        assert_eq!(direction, Direction::Up);
+    }
+
+    #[test]
+    fn test_path_finder_teleports() {
+        let mut map = test_map(12, &[(11, 11, Coin(1)), (11, 0, Coin(1))]);
+        for (width, height) in [(0, 1), (10, 11)] {
+            map[height][width] = Some(Tile {
+                tile_type: TileType::Teleport(true),
+                content: Content::None,
+                elevation: 0,
+            });
+        }
+        // a teleport that has not been activated yet
+        map[0][10] = Some(Tile {
+            tile_type: TileType::Teleport(false),
+            content: Content::None,
+            elevation: 0,
+        });
+        assert_eq!(
+            find_teleports(&map),
+            vec![MapCoordinate::new(0, 1), MapCoordinate::new(10, 11)]
+        );
+
+        let options = PathOptions {
+            teleport_cost: Some(1),
+            ..PathOptions::default()
+        };
+        let finder = PathFinder::new(&map, options);
+        let start = MapCoordinate::new(0, 0);
+        let path = finder.find(start, MapCoordinate::new(11, 11)).unwrap();
+        assert!(path.uses_teleports());
+        assert_eq!(path.len(), 3);
+        assert_eq!(
+            path.steps[1],
+            PathStep::Teleport(MapCoordinate::new(10, 11))
+        );
+        assert!(path.directions().is_none());
+
+        // the coin behind the teleport is closer than the one on the same row
+        let targets = [MapCoordinate::new(11, 0), MapCoordinate::new(11, 11)];
+        let path = finder.find_closest(start, &targets).unwrap();
+        assert_eq!(path.end(), Some(MapCoordinate::new(11, 11)));
+        assert_eq!(
+            finder.distance(start, MapCoordinate::new(11, 11)),
+            Some(
+                TileType::Teleport(true).properties().cost()
+                    + 1
+                    + TileType::Grass.properties().cost()
+            )
+        );
+
+        let finder = PathFinder::new(
+            &map,
+            PathOptions {
+                teleport_cost: None,
+                ..PathOptions::default()
+            },
+        );
+        assert!(finder.teleports().is_empty());
+        let path = finder.find_closest(start, &targets).unwrap();
+        assert_eq!(path.end(), Some(MapCoordinate::new(11, 0)));
+        assert!(!path.uses_teleports());
+    }
+
+    #[test]
+    fn test_cost_model() {
+        let rainy = EnvironmentalConditions::new(&vec![Rainy], 15, 12).unwrap();
+        let model = CostModel::from_conditions(&rainy);
+        assert_eq!(model.multiplier(), 1.5);
+        let with_forecast = model.clone().with_forecast(vec![Sunny, Sunny, Sunny]);
+        assert_eq!(with_forecast.multiplier(), 1.125);
+
+        // the richer tile is worth the trip on a sunny day, but not in the rain
+        let map = test_map(8, &[(1, 0, Rock(3)), (6, 0, Rock(11))]);
+        let resources = TileMapper::collection(&map).unwrap();
+        let origin = MapCoordinate::new(0, 0);
+        let weights = ScoringWeights::default();
+        let sunny = TileMapper::score_targets(&resources, &Rock(0), origin, &weights);
+        assert_eq!(sunny[0].coordinate, MapCoordinate::new(6, 0));
+        let weights = model.scoring_weights(&weights);
+        assert_eq!(weights.travel_weight, 1.5);
+        let rain = TileMapper::score_targets(&resources, &Rock(0), origin, &weights);
+        assert_eq!(rain[0].coordinate, MapCoordinate::new(1, 0));
+
+        let options = model.path_options(PathOptions::default());
+        let dry = PathFinder::new(&map, PathOptions::default());
+        let wet = PathFinder::new(&map, options);
+        let step = TileType::Grass.properties().cost();
+        let target = MapCoordinate::new(3, 0);
+        assert_eq!(dry.distance(origin, target), Some(3 * step));
+        assert_eq!(
+            wet.distance(origin, target),
+            Some(3 * (step as f64 * 1.5).ceil() as usize)
+        );
+    }
+
+    #[test]
+    fn test_map_sources() {
+        let map = test_map(6, &[(1, 1, Rock(3)), (4, 5, Rock(9)), (2, 3, Tree(2))]);
+        let mut index = ResourceIndex::new();
+        index.update(&map, 1).unwrap();
+        let resources = TileMapper::collection(&map).unwrap();
+        let replayed = ResourceIndex::from_map(resources.clone(), 1);
+
+        assert_eq!(TileMapper::collection(&map[..]), Some(resources.clone()));
+        assert_eq!(TileMapper::collection(&index), Some(resources.clone()));
+        assert_eq!(TileMapper::collection(&replayed), Some(resources.clone()));
+        assert_eq!(index.tiles().map(|tiles| tiles.len()), Some(6));
+        assert!(replayed.tiles().is_none());
+        assert!(MapSource::tiles(&resources).is_none());
+
+        // sources without a tile grid still give the resource figures
+        let full = TileMapper::statistics(&index).unwrap();
+        let partial = TileMapper::statistics(&resources).unwrap();
+        assert_eq!(full.get(&Rock(0)), partial.get(&Rock(0)));
+        assert_eq!(full.discovered_fraction(), 1.);
+        assert_eq!(partial.world_tiles(), 0);
+    }
+
+    #[test]
+    fn test_locatable_origins() {
+        let map = test_map(8, &[(0, 0, Coin(2)), (7, 7, Coin(5)), (6, 5, Bank(0..10))]);
+        let mapper = TileMapper {};
+
+        // the closest coin as seen from the bank, not from the robot
+        let bank = mapper
+            .find_closest(&map, &MapCoordinate::new(0, 1), Bank(0..0))
+            .unwrap();
+        assert_eq!(bank, MapCoordinate::new(6, 5));
+        assert_eq!(
+            mapper.find_closest(&map, &bank, Coin(0)).unwrap(),
+            MapCoordinate::new(7, 7)
+        );
+
+        let origin = MapCoordinate::from(Coordinate::new(1, 2));
+        assert_eq!(origin, MapCoordinate::new(2, 1));
+        assert_eq!(origin.location(), origin);
+        assert_eq!(
+            mapper.find_closest(&map, &origin, Coin(0)).unwrap(),
+            MapCoordinate::new(0, 0)
+        );
+        assert_eq!(
+            mapper
+                .find_most_loaded(&map, &MapCoordinate::from((1, 1)), Coin(0))
+                .unwrap(),
+            MapCoordinate::new(7, 7)
+        );
+
+        // coordinates and tuples are accepted directly
+        let coordinate = Coordinate::new(1, 2);
+        assert_eq!(coordinate.location(), origin);
+        assert_eq!(
+            mapper.find_closest(&map, &coordinate, Coin(0)).unwrap(),
+            MapCoordinate::new(0, 0)
+        );
+        let tuple: (usize, usize) = (6, 6);
+        assert_eq!(tuple.location(), MapCoordinate::new(6, 6));
+        assert_eq!(
+            mapper.find_closest(&map, &tuple, Coin(0)).unwrap(),
+            MapCoordinate::new(7, 7)
+        );
+        assert_eq!(
+            mapper.find_most_loaded(&map, &(0, 1), Coin(0)).unwrap(),
+            MapCoordinate::new(7, 7)
+        );
+    }
+
+    #[test]
+    fn test_tool_errors() {
+        let map = test_map(4, &[(1, 1, Rock(3))]);
+        let mapper = TileMapper {};
+        let origin = MapCoordinate::new(0, 0);
+
+        match mapper.find_closest(&map, &origin, Tree(0)) {
+            Err(error @ ToolError::ContentNotDiscovered(kind)) => {
+                assert_eq!(kind, mem::discriminant(&Tree(0)));
+                assert_eq!(error.to_string(), "Tree not discovered yet");
+                assert_eq!(format!("{:?}", error), "Tree not discovered yet");
+            }
+            _ => panic!("the tree should not be found"),
+        }
+        let outside = MapCoordinate::new(2, 9);
+        match mapper.find_path(&map, &origin, outside, PathOptions::default()) {
+            Err(ToolError::OutOfBounds { coordinate, size }) => {
+                assert_eq!((coordinate, size), (outside, 4))
+            }
+            _ => panic!("the target is outside the map"),
+        }
+        assert!(matches!(
+            ToolError::from(LibError::NotEnoughEnergy),
+            ToolError::Lib(LibError::NotEnoughEnergy)
+        ));
+
+        let path = std::env::temp_dir().join(format!(
+            "tile_mapper_corrupt_{}.history",
+            std::process::id()
+        ));
+        std::fs::write(&path, "100 + Rock 1,1 3\n200 ? Rock 1,1 3\n").unwrap();
+        let error = HistoryReplayer::open(&path).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        match &error {
+            ToolError::CorruptSave { line, .. } => assert_eq!(*line, Some(2)),
+            _ => panic!("the second line is invalid"),
+        }
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sub_underflow() {
+        // a signed difference needs `offset_to`
+        let _ = MapCoordinate::new(2, 0) - MapCoordinate::new(3, 1);
+    }
+
+    #[test]
+    fn test_offsets() {
+        let coordinates = MapCoordinate::new(2, 0);
+        assert_eq!(
+            coordinates.offset_to(&MapCoordinate::new(0, 3)),
+            Offset::new(-2, 3)
+        );
+
+        let up = Offset::from(Direction::Up);
+        assert_eq!(up, Offset::new(0, -1));
+        assert_eq!(up.direction(), Some(Direction::Up));
+        assert_eq!((up + up).direction(), None);
+        assert_eq!(-up, Offset::from(Direction::Down));
+        assert_eq!(Offset::new(-2, 3).manhattan_length(), 5);
+        assert_eq!(Offset::new(-2, 3).to_string(), "(-2, +3)");
+
+        assert_eq!(coordinates.checked_add(up, 3), None);
+        assert_eq!(
+            coordinates.checked_sub(up, 3),
+            Some(MapCoordinate::new(2, 1))
+        );
+        assert_eq!(coordinates.checked_add(Offset::new(1, 0), 3), None);
+        assert_eq!(
+            coordinates.saturating_add(Offset::new(5, -5), 3),
+            MapCoordinate::new(2, 0)
+        );
+        assert_eq!(
+            coordinates.saturating_sub(Offset::new(5, -5), 3),
+            MapCoordinate::new(0, 2)
+        );
+
+        let coordinate = coordinates.to_coordinate(3).unwrap();
+        assert_eq!((coordinate.get_row(), coordinate.get_col()), (0, 2));
+        assert_eq!(
+            MapCoordinate::from_coordinate(&coordinate, 3).unwrap(),
+            coordinates
+        );
+        assert!(coordinates.to_coordinate(2).is_err());
+    }
+
+    #[test]
+    fn test_regions() {
+        let center = MapCoordinate::new(1, 1);
+        assert_eq!(center.neighbours(3).count(), 4);
+        assert_eq!(center.neighbours8(3).count(), 8);
+        assert_eq!(
+            MapCoordinate::new(2, 2).neighbours8(3).collect::<Vec<_>>(),
+            vec![
+                MapCoordinate::new(1, 1),
+                MapCoordinate::new(2, 1),
+                MapCoordinate::new(1, 2)
+            ]
+        );
+        assert_eq!(center.ring(0, 3).collect::<Vec<_>>(), vec![center]);
+        assert_eq!(center.ring(2, 4).count(), 7);
+
+        let line: Vec<MapCoordinate> = MapCoordinate::new(4, 3)
+            .line_to(MapCoordinate::new(0, 1))
+            .collect();
+        assert_eq!(line.len(), 5);
+        assert_eq!(line[0], MapCoordinate::new(4, 3));
+        assert_eq!(line[4], MapCoordinate::new(0, 1));
+        assert!(line
+            .windows(2)
+            .all(|pair| pair[0].offset_to(&pair[1]).get_width() == -1));
+
+        let region = Rect::around(MapCoordinate::new(4, 4), 1, 5).unwrap();
+        assert_eq!(
+            region,
+            Rect::new(MapCoordinate::new(4, 4), center + center + center)
+        );
+        assert_eq!(region.area(), 4);
+        assert_eq!(region.iter().next(), Some(MapCoordinate::new(3, 3)));
+        assert_eq!(
+            region.intersection(&Rect::new(
+                MapCoordinate::new(0, 0),
+                MapCoordinate::new(3, 3)
+            )),
+            Some(Rect::new(
+                MapCoordinate::new(3, 3),
+                MapCoordinate::new(3, 3)
+            ))
+        );
+        assert_eq!(Rect::whole(5).unwrap().area(), 25);
+        assert!(Rect::around(MapCoordinate::new(5, 0), 1, 5).is_none());
+
+        // queries only see the tiles inside the region
+        let map = test_map(5, &[(0, 0, Rock(1)), (4, 4, Rock(1))]);
+        let mapper = TileMapper {};
+        let bottom = Rect::new(MapCoordinate::new(0, 3), MapCoordinate::new(4, 4));
+        assert_eq!(
+            mapper
+                .find_closest(&map.within(bottom), &MapCoordinate::new(0, 1), Rock(0))
+                .unwrap(),
+            MapCoordinate::new(4, 4)
+        );
+        let path = mapper
+            .find_path(
+                &map.within(bottom),
+                &MapCoordinate::new(0, 3),
+                MapCoordinate::new(4, 4),
+                PathOptions::default(),
+            )
+            .unwrap();
+        assert!(path.tiles.iter().all(|tile| bottom.contains(tile)));
+    }
+
+    #[test]
+    fn test_spatial_index() {
+        // a pseudo-random map large enough for the buckets to be used
+        let mut seed: usize = 7;
+        let mut tiles = vec![];
+        for _ in 0..300 {
+            seed = (seed * 1103515245 + 12345) % 2147483648;
+            let coordinate = MapCoordinate::new(seed % 200, (seed / 200) % 200);
+            if !tiles.iter().any(|(c, _)| *c == coordinate) {
+                tiles.push((coordinate, (Some(seed % 10), None)));
+            }
+        }
+        tiles.sort_by(|a: &(MapCoordinate, ContentQuantity), b| a.0.cmp(&b.0));
+        let mut resources = HashMap::new();
+        resources.insert(mem::discriminant(&Coin(0)), tiles.clone());
+        let spatial = SpatialIndex::from_resources(&resources);
+        assert_eq!(spatial.len(&Coin(0)), tiles.len());
+        assert!(spatial
+            .nearest(&Rock(0), &MapCoordinate::new(0, 0))
+            .is_none());
+
+        let by_distance = |origin: MapCoordinate| {
+            let mut sorted = tiles.clone();
+            sorted.sort_by(|a, b| {
+                a.0.get_distance(&origin)
+                    .total_cmp(&b.0.get_distance(&origin))
+                    .then(a.0.cmp(&b.0))
+            });
+            sorted
+        };
+        for origin in [
+            MapCoordinate::new(0, 0),
+            MapCoordinate::new(100, 57),
+            MapCoordinate::new(199, 3),
+            MapCoordinate::new(400, 400),
+        ] {
+            let expected = by_distance(origin);
+            let mapper = TileMapper {};
+            assert_eq!(
+                spatial.nearest(&Coin(0), &origin).map(|(c, _)| *c),
+                Some(expected[0].0)
+            );
+            assert_eq!(
+                mapper.find_closest(&resources, &origin, Coin(0)).unwrap(),
+                expected[0].0
+            );
+            let five: Vec<MapCoordinate> = spatial
+                .k_nearest(&Coin(0), &origin, 5)
+                .into_iter()
+                .map(|(c, _)| *c)
+                .collect();
+            let expected_five: Vec<MapCoordinate> =
+                expected.iter().take(5).map(|(c, _)| *c).collect();
+            assert_eq!(five, expected_five);
+            let in_range = spatial.in_range(&Coin(0), &origin, 30.);
+            assert_eq!(
+                in_range.len(),
+                expected
+                    .iter()
+                    .filter(|(c, _)| c.get_distance(&origin) <= 30.)
+                    .count()
+            );
+        }
+
+        let region = Rect::new(MapCoordinate::new(10, 10), MapCoordinate::new(60, 40));
+        let inside = spatial.in_rect(&Coin(0), &region);
+        assert_eq!(
+            inside.len(),
+            tiles.iter().filter(|(c, _)| region.contains(c)).count()
+        );
+        assert!(inside.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        let mut spatial = spatial;
+        let (first, quantity) = tiles[0].clone();
+        assert_eq!(spatial.remove(&Coin(0), &first), Some(quantity));
+        assert_eq!(spatial.remove(&Coin(0), &first), None);
+        assert_ne!(
+            spatial.nearest(&Coin(0), &first).map(|(c, _)| *c),
+            Some(first)
+        );
+    }
+
+    #[test]
+    fn test_find_closest_index_matches_grid() {
+        let mapper = TileMapper {};
+        assert!(matches!(
+            mapper.find_closest(&ResourceIndex::new(), &MapCoordinate::new(0, 0), Rock(0)),
+            Err(ToolError::WorldNotDiscovered)
+        ));
+
+        // rocks 10 tiles away from (30, 30) in every direction, and enough rocks on the last row
+        // for the index to use its buckets
+        let mut contents = vec![];
+        for (dw, dh) in [(0, 10), (6, 8), (8, 6), (10, 0)] {
+            for (w, h) in [
+                (30 + dw, 30 + dh),
+                (30 - dw, 30 + dh),
+                (30 + dw, 30 - dh),
+                (30 - dw, 30 - dh),
+            ] {
+                contents.push((w, h, Rock(1)));
+            }
+        }
+        contents.extend((0..40).map(|w| (w, 63, Rock(1))));
+        let map = test_map(64, &contents);
+        let mut index = ResourceIndex::new();
+        index.update_from_map(map.clone(), 1).unwrap();
+        assert!(matches!(
+            mapper.find_closest(&index, &MapCoordinate::new(0, 0), Tree(0)),
+            Err(ToolError::ContentNotDiscovered(_))
+        ));
+
+        for origin in [
+            MapCoordinate::new(30, 30),
+            MapCoordinate::new(30, 21),
+            MapCoordinate::new(20, 40),
+            MapCoordinate::new(15, 63),
+        ] {
+            let from_grid = mapper.find_closest(&map, &origin, Rock(0)).unwrap();
+            assert_eq!(
+                mapper.find_closest(&index, &origin, Rock(0)).unwrap(),
+                from_grid
+            );
+        }
+        // the first of the rocks at the same distance in reading order
+        assert_eq!(
+            mapper
+                .find_closest(&index, &MapCoordinate::new(30, 30), Rock(0))
+                .unwrap(),
+            MapCoordinate::new(30, 20)
+        );
+        assert_eq!(
+            mapper
+                .find_closest(&index, &MapCoordinate::new(15, 63), Rock(0))
+                .unwrap(),
+            MapCoordinate::new(15, 63)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_parallel_scan() {
+        use crate::tool::tile_mapper::{scan_parallel, scan_sequential};
+
+        let mut contents = vec![];
+        for i in 0..500 {
+            let content = if i % 3 == 0 { Rock(i % 7) } else { Tree(i % 5) };
+            contents.push((i * 7 % 97, i * 13 % 97, content));
+        }
+        let map = test_map(97, &contents);
+        let sequential = scan_sequential(&map);
+        assert_eq!(scan_parallel(&map), sequential);
+        assert!(sequential
+            .values()
+            .all(|tiles| tiles.windows(2).all(|pair| pair[0].0 < pair[1].0)));
+    }
+
+    #[test]
+    fn test_resource_entries() {
+        let map = test_map(
+            6,
+            &[
+                (1, 0, Rock(2)),
+                (4, 3, Rock(5)),
+                (2, 2, Coin(3)),
+                (0, 5, Bin(2..10)),
+            ],
+        );
+        let resources = TileMapper::collection(&map).unwrap();
+
+        let from_grid: Vec<(MapCoordinate, ContentQuantity)> = grid_entries(&map, &Rock(0))
+            .map(|entry| (entry.coordinate(), entry.quantity()))
+            .collect();
+        let from_map: Vec<(MapCoordinate, ContentQuantity)> = map_entries(&resources, &Rock(0))
+            .map(|entry| (entry.coordinate(), entry.quantity()))
+            .collect();
+        assert_eq!(
+            from_grid,
+            vec![
+                (MapCoordinate::new(1, 0), (Some(2), None)),
+                (MapCoordinate::new(4, 3), (Some(5), None))
+            ]
+        );
+        assert_eq!(from_grid, from_map);
+
+        let bin = grid_entries(&map, &Bin(0..0)).next().unwrap();
+        assert_eq!(bin.kind(), mem::discriminant(&Bin(0..0)));
+        assert_eq!(bin.content(), Some(&Bin(2..10)));
+        assert_eq!(bin.amount(), 2);
+        assert_eq!(
+            map_entries(&resources, &Bin(0..0))
+                .next()
+                .unwrap()
+                .content(),
+            None
+        );
+
+        // tiles without content are not entries, as in `collection`
+        assert_eq!(grid_entries(&map, &Content::None).count(), 0);
+    }
+
+    #[test]
+    fn test_iter_resources() {
+        let map = test_map(
+            8,
+            &[
+                (5, 0, Coin(1)),
+                (1, 2, Coin(2)),
+                (6, 2, Rock(4)),
+                (0, 4, Coin(3)),
+                (7, 7, Coin(4)),
+            ],
+        );
+
+        let all: Vec<MapCoordinate> = TileMapper::iter_resources(&map)
+            .map(|(coordinate, _, _)| coordinate)
+            .collect();
+        assert_eq!(all.len(), 5);
+
+        // the grid kept by an index can be iterated too
+        let mut index = ResourceIndex::new();
+        index.update(&map, 1).unwrap();
+        let indexed: Vec<MapCoordinate> =
+            TileMapper::iter_resources(index.tiles().unwrap_or_default())
+                .map(|(coordinate, _, _)| coordinate)
+                .collect();
+        assert_eq!(indexed, all);
+        // the items borrow the grid
+        let (_, content, tile) = TileMapper::iter_resources(&map).next().unwrap();
+        assert!(std::ptr::eq(tile, map[0][5].as_ref().unwrap()));
+        assert!(std::ptr::eq(content, &tile.content));
+
+        let first_coins: Vec<(MapCoordinate, Content)> = TileMapper::iter_resources(&map)
+            .of_kind(&Coin(0))
+            .take(3)
+            .map(|(coordinate, content, tile)| {
+                assert!(matches!(tile.tile_type, TileType::Grass));
+                (coordinate, content.clone())
+            })
+            .collect();
+        assert_eq!(
+            first_coins,
+            vec![
+                (MapCoordinate::new(5, 0), Coin(1)),
+                (MapCoordinate::new(1, 2), Coin(2)),
+                (MapCoordinate::new(0, 4), Coin(3))
+            ]
+        );
+
+        let region = Rect::new(MapCoordinate::new(0, 2), MapCoordinate::new(7, 7));
+        assert_eq!(
+            TileMapper::iter_resources(&map)
+                .of_kind(&Coin(0))
+                .within(region)
+                .closest_to(&MapCoordinate::new(7, 0)),
+            Some(MapCoordinate::new(1, 2))
+        );
+        // disjoint filters leave nothing
+        assert_eq!(
+            TileMapper::iter_resources(&map)
+                .of_kind(&Coin(0))
+                .of_kind(&Rock(0))
+                .count(),
+            0
+        );
+
+        // the remaining tiles can be passed to the other queries
+        let coins = TileMapper::iter_resources(&map)
+            .of_kind(&Coin(0))
+            .within(region)
+            .into_resource_map();
+        assert_eq!(coins.len(), 1);
+        assert_eq!(
+            TileMapper {}
+                .find_most_loaded(&coins, &MapCoordinate::new(0, 0), Coin(0))
+                .unwrap(),
+            MapCoordinate::new(7, 7)
+        );
+    }
+
+    #[test]
+    fn test_discovery_mask() {
+        // a 10x10 map where only the 3x3 square in the top left corner is discovered
+        let mut map = test_map(10, &[(1, 1, Rock(2))]);
+        for (row, row_vector) in map.iter_mut().enumerate() {
+            for (column, tile) in row_vector.iter_mut().enumerate() {
+                if row > 2 || column > 2 {
+                    *tile = None;
+                }
+            }
+        }
+
+        let mask = DiscoveryMask::from_tiles(&map);
+        assert_eq!(mask.size(), 10);
+        assert_eq!(mask.discovered_count(), 9);
+        assert_eq!(mask.coverage(), 9.);
+        assert!(mask.is_discovered(&MapCoordinate::new(2, 2)));
+        assert!(!mask.is_discovered(&MapCoordinate::new(3, 2)));
+        assert!(!mask.is_discovered(&MapCoordinate::new(20, 0)));
+
+        let corner = Rect::new(MapCoordinate::new(0, 0), MapCoordinate::new(5, 2));
+        assert_eq!(mask.region_coverage(&corner), 50.);
+
+        let frontier: Vec<MapCoordinate> = mask.frontier().collect();
+        assert_eq!(
+            frontier,
+            vec![
+                MapCoordinate::new(2, 0),
+                MapCoordinate::new(2, 1),
+                MapCoordinate::new(0, 2),
+                MapCoordinate::new(1, 2),
+                MapCoordinate::new(2, 2)
+            ]
+        );
+
+        let unexplored = mask.unexplored_regions(5);
+        assert_eq!(unexplored.len(), 3);
+        assert!(!unexplored.contains(&Rect::new(
+            MapCoordinate::new(0, 0),
+            MapCoordinate::new(4, 4)
+        )));
+        assert_eq!(mask.unexplored_regions(3).len(), 15);
+
+        // the index keeps the mask up to date, and discovery is never undone
+        let mut index = ResourceIndex::new();
+        index.update_from_map(map.clone(), 1).unwrap();
+        assert_eq!(index.discovered(), &mask);
+        map[0][0] = None;
+        map[5][5] = test_map(1, &[])[0][0].clone();
+        index.update_from_map(map, 2).unwrap();
+        assert_eq!(index.discovered().discovered_count(), 10);
+        assert!(index.discovered().is_discovered(&MapCoordinate::new(0, 0)));
+
+        // path finding treats the tiles missing from the mask as undiscovered
+        let grid = test_map(3, &[]);
+        let mut top_row = DiscoveryMask::new(3);
+        for width in 0..3 {
+            top_row.discover(&MapCoordinate::new(width, 0));
+        }
+        let start = MapCoordinate::new(0, 0);
+        let finder = PathFinder::new(&grid, PathOptions::default()).with_discovered(&top_row);
+        assert!(finder.find(start, MapCoordinate::new(2, 0)).is_some());
+        assert!(finder.find(start, MapCoordinate::new(0, 2)).is_none());
+        assert!(PathFinder::new(&grid, PathOptions::default())
+            .find(start, MapCoordinate::new(0, 2))
+            .is_some());
+    }
+
+    #[test]
+    fn test_resource_observers() {
+        // subscriptions do not keep the index from moving across threads
+        fn assert_send<T: Send>() {}
+        assert_send::<ResourceIndex>();
+
+        let mut index = ResourceIndex::new();
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let log = Arc::clone(&seen);
+        let all = index.subscribe(ResourceFilter::new(), move |entry| {
+            log.lock().unwrap().push(entry.coordinate())
+        });
+        let (_, markets) = index.subscribe_channel(
+            ResourceFilter::new()
+                .content(&Market(0))
+                .content(&Bank(0..0))
+                .min_quantity(2)
+                .within(Rect::new(
+                    MapCoordinate::new(0, 0),
+                    MapCoordinate::new(4, 4),
+                )),
+        );
+
+        index
+            .update_from_map(
+                test_map(
+                    8,
+                    &[(1, 1, Market(3)), (2, 2, Market(1)), (6, 6, Market(5))],
+                ),
+                1,
+            )
+            .unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 3);
+        let received: Vec<DiffEntry> = markets.try_iter().collect();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].coordinate(), MapCoordinate::new(1, 1));
+
+        // the market at (2, 2) now passes the threshold, the one at (1, 1) is gone
+        assert!(index.unsubscribe(all));
+        assert!(!index.unsubscribe(all));
+        index
+            .update_from_map(
+                test_map(8, &[(2, 2, Market(2)), (6, 6, Market(5)), (3, 0, Rock(4))]),
+                2,
+            )
+            .unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 3);
+        let received: Vec<DiffEntry> = markets.try_iter().collect();
+        assert_eq!(received.len(), 2);
+        assert!(matches!(received[0], DiffEntry::Removed { .. }));
+        assert_eq!(
+            received[1],
+            DiffEntry::Changed {
+                kind: mem::discriminant(&Market(0)),
+                coordinate: MapCoordinate::new(2, 2),
+                before: (Some(1), None),
+                after: (Some(2), None),
+            }
+        );
+
+        // dropping the receiver cancels the subscription
+        drop(markets);
+        index
+            .update_from_map(test_map(8, &[(1, 1, Market(9))]), 3)
+            .unwrap();
+        let (_, receiver) = index.subscribe_channel(ResourceFilter::new());
+        index.update_from_map(test_map(8, &[]), 4).unwrap();
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+
+    /// A 6x6 map with water on the left half and grass on the right half, where only the
+    /// first three rows are discovered: fish live in the water, trees on the grass.
+    fn half_discovered_map() -> Vec<Vec<Option<Tile>>> {
+        let mut map = test_map(6, &[(3, 0, Tree(1)), (4, 1, Tree(1)), (5, 2, Tree(1))]);
+        for (row, row_vector) in map.iter_mut().enumerate() {
+            for (column, tile) in row_vector.iter_mut().enumerate() {
+                if row > 2 {
+                    *tile = None;
+                } else if column < 3 {
+                    *tile = Some(Tile {
+                        tile_type: TileType::ShallowWater,
+                        content: if row == column {
+                            Content::Fish(2 * row + 2)
+                        } else {
+                            Content::None
+                        },
+                        elevation: 0,
+                    });
+                }
+            }
+        }
+        map
+    }
+
+    /// Priors for `half_discovered_map`: fish in water, markets on grass and rocks on sand.
+    fn prediction_priors() -> Vec<(TileType, Content, f64)> {
+        vec![
+            (TileType::ShallowWater, Content::Fish(0), 0.1),
+            (TileType::Grass, Market(0), 0.005),
+            (TileType::Sand, Rock(0), 0.05),
+        ]
+    }
+
+    #[test]
+    fn test_prediction_model() {
+        let map = half_discovered_map();
+        let model = PredictionModel::train_with_priors(&map, &prediction_priors());
+        // 3 fish on 9 water tiles, smoothed with the prior of 0.1 worth one tile
+        let fish_in_water = (3. + 0.1) / 10.;
+        assert_eq!(
+            model.probability(&TileType::ShallowWater, &Content::Fish(0)),
+            fish_in_water
+        );
+        assert_eq!(model.probability(&TileType::Grass, &Content::Fish(0)), 0.);
+        assert_eq!(model.probability(&TileType::Sand, &Tree(0)), 0.);
+        // no sand was discovered: the prior alone
+        assert_eq!(model.probability(&TileType::Sand, &Rock(0)), 0.05);
+        assert_eq!(model.mean_amount(&Content::Fish(0)), 4.);
+        assert_eq!(model.mean_amount(&Market(0)), 1.);
+
+        let unsmoothed =
+            PredictionModel::train_with_priors(&map, &prediction_priors()).with_prior_weight(0.);
+        assert_eq!(
+            unsmoothed.probability(&TileType::ShallowWater, &Content::Fish(0)),
+            1. / 3.
+        );
+        assert_eq!(unsmoothed.probability(&TileType::Sand, &Rock(0)), 0.);
+
+        // discovered tiles are certain
+        assert_eq!(
+            model.estimate(&map, &MapCoordinate::new(1, 1), &Content::Fish(0)),
+            1.
+        );
+        assert_eq!(
+            model.estimate(&map, &MapCoordinate::new(1, 0), &Content::Fish(0)),
+            0.
+        );
+        // only water was discovered around (0, 4), nothing around (5, 5)
+        assert_eq!(
+            model.estimate(&map, &MapCoordinate::new(0, 4), &Content::Fish(0)),
+            fish_in_water
+        );
+        assert_eq!(
+            model.estimate(&map, &MapCoordinate::new(0, 4), &Tree(0)),
+            0.
+        );
+        assert_eq!(
+            model.estimate(&map, &MapCoordinate::new(5, 5), &Content::Fish(0)),
+            0.5 * fish_in_water
+        );
+        let distribution = model.distribution(&map, &MapCoordinate::new(0, 4));
+        assert_eq!(
+            distribution,
+            vec![(mem::discriminant(&Content::Fish(0)), fish_in_water)]
+        );
+
+        let prediction = model.predict(&map, &Content::Fish(0));
+        assert_eq!(prediction.probabilities().len(), 6);
+        assert_eq!(prediction.expected_quantity(&MapCoordinate::new(2, 2)), 6.);
+        assert_eq!(
+            prediction.expected_quantity(&MapCoordinate::new(0, 4)),
+            fish_in_water * 4.
+        );
+        assert!(prediction.expected_undiscovered() > 0.);
+        assert_eq!(
+            prediction.explore_target(&MapCoordinate::new(0, 5)),
+            Some((MapCoordinate::new(0, 4), fish_in_water))
+        );
+
+        // a market was never discovered, but markets can be on grass
+        let (_, probability) = model
+            .predict(&map, &Market(0))
+            .explore_target(&MapCoordinate::new(0, 5))
+            .unwrap();
+        assert!(probability > 0.);
+        assert_eq!(
+            model
+                .predict(&map, &Content::Scarecrow)
+                .explore_target(&MapCoordinate::new(0, 5)),
+            None
+        );
+        assert_eq!(
+            unsmoothed
+                .predict(&map, &Market(0))
+                .explore_target(&MapCoordinate::new(0, 5)),
+            None
+        );
+
+        // without priors, every kind of content is as likely on every tile type
+        let uniform = PredictionModel::train(&map);
+        let prior = 1. / 15.;
+        assert_eq!(uniform.probability(&TileType::Sand, &Rock(0)), prior);
+        assert_eq!(
+            uniform.probability(&TileType::ShallowWater, &Content::Fish(0)),
+            (3. + prior) / 10.
+        );
+        assert_eq!(
+            uniform.probability(&TileType::Grass, &Content::Fish(0)),
+            prior / 10.
+        );
+        assert!(uniform
+            .predict(&map, &Content::Scarecrow)
+            .explore_target(&MapCoordinate::new(0, 5))
+            .is_some());
+    }
+
+    #[test]
+    fn test_find_or_predict() {
+        let mapper = TileMapper {};
+        let origin = MapCoordinate::new(0, 5);
+        let explored = half_discovered_map();
+        let model = PredictionModel::train_with_priors(&explored, &prediction_priors());
+
+        assert_eq!(
+            mapper
+                .find_or_predict(&explored, &origin, Content::Fish(0), &model)
+                .unwrap(),
+            Lead::Discovered(MapCoordinate::new(2, 2))
+        );
+
+        // the same landscape without fish: the model learnt elsewhere still points at the water
+        let mut fresh = explored.clone();
+        for tile in fresh.iter_mut().flatten().flatten() {
+            tile.content = Content::None;
+        }
+        assert_eq!(
+            mapper
+                .find_or_predict(&fresh, &origin, Content::Fish(0), &model)
+                .unwrap(),
+            Lead::Predicted {
+                coordinate: MapCoordinate::new(0, 4),
+                probability: (3. + 0.1) / 10.
+            }
+        );
+        // so does a model trained on it, from the prior alone
+        let untrained = PredictionModel::train_with_priors(&fresh, &prediction_priors());
+        assert_eq!(
+            mapper
+                .find_or_predict(&fresh, &origin, Content::Fish(0), &untrained)
+                .unwrap(),
+            Lead::Predicted {
+                coordinate: MapCoordinate::new(0, 4),
+                probability: (0. + 0.1) / 10.
+            }
+        );
+        assert!(matches!(
+            mapper.find_or_predict(&fresh, &origin, Content::Scarecrow, &model),
+            Err(ToolError::ContentNotDiscovered(_))
+        ));
+    }
+
+    #[test]
+    fn test_depletion_tracker() {
+        let mut tracker = DepletionTracker::new();
+        let before = test_map(4, &[(0, 0, Tree(10)), (1, 0, Tree(4)), (2, 0, Rock(3))]);
+        let after = test_map(
+            4,
+            &[
+                (0, 0, Tree(6)),
+                (2, 0, Rock(3)),
+                (0, 3, Tree(20)),
+                (3, 3, Garbage(2)),
+            ],
+        );
+        tracker.observe(&before, 0).unwrap();
+        assert_eq!(tracker.depletion_rate(&Tree(0)), None);
+        tracker.observe(&after, 2).unwrap();
+        // observing the same tick again changes nothing
+        tracker.observe(&after, 2).unwrap();
+
+        // both trees lost 4 units in 2 ticks, the second one being cut down
+        assert_eq!(tracker.depletion_rate(&Tree(0)), Some(-2.));
+        assert_eq!(
+            tracker.depletion_rate_on(&Tree(0), &TileType::Grass),
+            Some(-2.)
+        );
+        assert_eq!(tracker.depletion_rate_on(&Tree(0), &TileType::Sand), None);
+        assert_eq!(tracker.depletion_rate(&Rock(0)), Some(0.));
+        // 13 empty tiles for 2 ticks, garbage showed up once
+        assert_eq!(
+            tracker.appearance_rate(&Garbage(0), &TileType::Grass),
+            Some(1. / 26.)
+        );
+
+        let tree = MapCoordinate::new(0, 0);
+        assert_eq!(tracker.projected_quantity(&tree, &Tree(0), 4), Some(2.));
+        assert_eq!(tracker.projected_quantity(&tree, &Tree(0), 10), Some(0.));
+        assert_eq!(tracker.projected_quantity(&tree, &Rock(0), 4), Some(0.));
+        assert!(tracker.likely_empty(&tree, &Tree(0), 5));
+        assert!(!tracker.likely_empty(&tree, &Tree(0), 4));
+        assert_eq!(
+            tracker.projected_quantity(&MapCoordinate::new(2, 2), &Garbage(0), 15),
+            Some(1.)
+        );
+        assert_eq!(
+            tracker.projected_quantity(&MapCoordinate::new(9, 9), &Garbage(0), 15),
+            None
+        );
+
+        // the closest tree would be gone by the time the robot gets there
+        let mapper = TileMapper {};
+        let origin = MapCoordinate::new(3, 0);
+        assert_eq!(mapper.find_closest(&after, &origin, Tree(0)).unwrap(), tree);
+        assert_eq!(
+            mapper
+                .find_closest_lasting(&after, &origin, Tree(0), &tracker, 2)
+                .unwrap(),
+            MapCoordinate::new(0, 3)
+        );
+        assert!(matches!(
+            mapper.find_closest_lasting(&after, &origin, Tree(0), &tracker, 30),
+            Err(ToolError::ContentNotDiscovered(_))
+        ));
+    }
+
+    #[test]
+    fn test_solve_goals() {
+        let map = test_map(
+            10,
+            &[
+                (3, 0, Tree(5)),
+                (6, 0, Tree(10)),
+                (0, 4, Rock(4)),
+                (2, 2, Garbage(3)),
+                (5, 5, Bin(0..10)),
+                (9, 9, Bank(0..20)),
+            ],
+        );
+        let mapper = TileMapper {};
+        let origin = MapCoordinate::new(0, 0);
+        let goals = [
+            Goal::Gather {
+                content: Tree(0),
+                amount: 12,
+            },
+            Goal::Gather {
+                content: Rock(0),
+                amount: 4,
+            },
+            Goal::Deliver {
+                content: Garbage(0),
+                amount: 3,
+            },
+        ];
+        let constraints = Constraints::new(1000, 30).ending(EndLocation::Near(Bank(0..0)));
+
+        let plan = mapper
+            .solve_goals(&map, &origin, &goals, &constraints)
+            .unwrap();
+        let collected = |kind: Content| -> usize {
+            plan.actions
+                .iter()
+                .filter_map(|action| match action {
+                    Action::Collect {
+                        content, amount, ..
+                    } if mem::discriminant(content) == mem::discriminant(&kind) => Some(*amount),
+                    _ => None,
+                })
+                .sum()
+        };
+        assert_eq!(collected(Tree(0)), 15);
+        assert_eq!(collected(Rock(0)), 4);
+        assert_eq!(collected(Garbage(0)), 3);
+
+        let collect_garbage = plan
+            .actions
+            .iter()
+            .position(|action| matches!(action, Action::Collect { target, .. } if *target == MapCoordinate::new(2, 2)))
+            .unwrap();
+        let deliver = plan
+            .actions
+            .iter()
+            .position(|action| matches!(action, Action::Deliver { .. }))
+            .unwrap();
+        assert!(collect_garbage < deliver);
+        assert!(matches!(
+            &plan.actions[deliver],
+            Action::Deliver { target, content: Garbage(_), amount: 3, .. }
+                if *target == MapCoordinate::new(5, 5)
+        ));
+
+        // the plan ends next to the bank, and its cost adds up
+        assert_eq!(
+            plan.end
+                .offset_to(&MapCoordinate::new(9, 9))
+                .manhattan_length(),
+            1
+        );
+        let moves: usize = plan
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Move(path) => Some(path.cost),
+                _ => None,
+            })
+            .sum();
+        let interactions = plan.len()
+            - plan
+                .actions
+                .iter()
+                .filter(|a| matches!(a, Action::Move(_)))
+                .count();
+        assert_eq!(interactions, 5);
+        assert_eq!(plan.cost, moves + 5 * constraints.interaction_cost);
+
+        // garbage already in the backpack is delivered without collecting more
+        let carrying = Constraints::new(1000, 30).carrying(&Garbage(0), 3);
+        let plan = mapper
+            .solve_goals(&map, &origin, &goals[2..], &carrying)
+            .unwrap();
+        assert!(matches!(
+            plan.actions[..],
+            [Action::Move(_), Action::Deliver { amount: 3, .. }]
+        ));
+
+        // what the backpack cannot hold is collected from the same tile after a delivery
+        let map = test_map(10, &[(2, 2, Garbage(8)), (5, 5, Bin(0..10))]);
+        let goals = [Goal::Deliver {
+            content: Garbage(0),
+            amount: 8,
+        }];
+        let plan = mapper
+            .solve_goals(&map, &origin, &goals, &Constraints::new(1000, 5))
+            .unwrap();
+        let interactions: Vec<(bool, MapCoordinate, usize)> = plan
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Collect { target, amount, .. } => Some((false, *target, *amount)),
+                Action::Deliver { target, amount, .. } => Some((true, *target, *amount)),
+                Action::Move(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            interactions,
+            vec![
+                (false, MapCoordinate::new(2, 2), 5),
+                (true, MapCoordinate::new(5, 5), 5),
+                (false, MapCoordinate::new(2, 2), 3),
+                (true, MapCoordinate::new(5, 5), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solve_goals_unmet() {
+        let map = test_map(10, &[(3, 0, Tree(5)), (6, 0, Tree(10))]);
+        let mapper = TileMapper {};
+        let origin = MapCoordinate::new(0, 0);
+        let trees = [Goal::Gather {
+            content: Tree(0),
+            amount: 12,
+        }];
+
+        let coins = [Goal::Gather {
+            content: Coin(0),
+            amount: 1,
+        }];
+        let error = mapper
+            .solve_goals(&map, &origin, &coins, &Constraints::new(1000, 30))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Cannot gather 1 Coin: Coin not discovered yet"
+        );
+        assert!(matches!(
+            error,
+            ToolError::GoalUnmet { ref goal, ref reason }
+                if *goal == coins[0].to_string() && matches!(**reason, ToolError::ContentNotDiscovered(_))
+        ));
+        assert!(error.source().is_some());
+
+        assert!(matches!(
+            mapper.solve_goals(&map, &origin, &trees, &Constraints::new(1, 30)),
+            Err(ToolError::GoalUnmet { reason, .. })
+                if matches!(*reason, ToolError::InsufficientEnergy { available: 1, .. })
+        ));
+        assert!(matches!(
+            mapper.solve_goals(&map, &origin, &trees, &Constraints::new(1000, 8)),
+            Err(ToolError::GoalUnmet { reason, .. }) if matches!(*reason, ToolError::Other(_))
+        ));
+        assert!(matches!(
+            mapper.solve_goals(
+                &map,
+                &origin,
+                &trees,
+                &Constraints::new(1000, 30).ending(EndLocation::At(MapCoordinate::new(20, 20)))
+            ),
+            Err(ToolError::Unreachable(_))
+        ));
+        // the first tree cannot be reached without entering the avoided tiles
+        let avoiding = Constraints::new(1000, 30)
+            .avoiding(MapCoordinate::new(2, 0))
+            .avoiding(MapCoordinate::new(4, 0))
+            .avoiding(MapCoordinate::new(3, 1));
+        assert!(matches!(
+            mapper.solve_goals(&map, &origin, &trees, &avoiding),
+            Err(ToolError::GoalUnmet { reason, .. })
+                if matches!(*reason, ToolError::ContentUnreachable(_))
+        ));
+        // nothing to do
+        assert!(mapper
+            .solve_goals(&map, &origin, &[], &Constraints::new(0, 0))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_plan_executor() {
+        struct TestRobot(Robot);
+        impl Runnable for TestRobot {
+            fn process_tick(&mut self, world: &mut World) {
+                // discover the trees around the robot
+                for dir in [Direction::Right, Direction::Down] {
+                    go(self, world, dir).expect("");
+                }
+
+                let mut index = ResourceIndex::new();
+                let goals = vec![Goal::Gather {
+                    content: Tree(0),
+                    amount: 4,
+                }];
+                let mut executor = PlanExecutor::new(goals.clone());
+                assert_eq!(executor.remaining_goals(), goals);
+
+                let mut status = ExecutionStatus::Running;
+                for tick in 0..10 {
+                    status = executor.step(self, world, &mut index, tick).unwrap();
+                    if status == ExecutionStatus::Done {
+                        break;
+                    }
+                }
+                assert_eq!(status, ExecutionStatus::Done);
+                assert_eq!(executor.replans(), 0);
+                assert_eq!(executor.remaining_steps(), 0);
+                assert!(executor.last_failure().is_none());
+                let plan = executor.plan().unwrap();
+                assert_eq!(plan.len(), 2);
+                assert!(plan
+                    .actions
+                    .iter()
+                    .all(|action| matches!(action, Action::Collect { .. })));
+                let trees = self
+                    .get_backpack()
+                    .get_contents()
+                    .get(&Tree(0))
+                    .copied()
+                    .unwrap_or(0);
+                assert!(trees >= 4);
+
+                // nothing left to do
+                assert_eq!(
+                    executor.step(self, world, &mut index, 10).unwrap(),
+                    ExecutionStatus::Done
+                );
+
+                // a goal that cannot be met is reported, not retried
+                let mut executor = PlanExecutor::new(vec![Goal::Gather {
+                    content: Coin(0),
+                    amount: 1,
+                }]);
+                match executor.step(self, world, &mut index, 11) {
+                    Err(ToolError::GoalUnmet { reason, .. }) => {
+                        assert!(matches!(*reason, ToolError::ContentNotDiscovered(_)))
+                    }
+                    other => panic!("unexpected result: {:?}", other),
+                }
+            }
+            fn handle_event(&mut self, event: Event) {
+                println!();
+                println!("{:?}", event);
+                println!();
+            }
+            fn get_energy(&self) -> &Energy {
+                &self.0.energy
+            }
+            fn get_energy_mut(&mut self) -> &mut Energy {
+                &mut self.0.energy
+            }
+            fn get_coordinate(&self) -> &Coordinate {
+                &self.0.coordinate
+            }
+            fn get_coordinate_mut(&mut self) -> &mut Coordinate {
+                &mut self.0.coordinate
+            }
+            fn get_backpack(&self) -> &BackPack {
+                &self.0.backpack
+            }
+            fn get_backpack_mut(&mut self) -> &mut BackPack {
+                &mut self.0.backpack
+            }
+        }
+
+        struct WorldGenerator {
+            size: usize,
+            tile_type: TileType,
+        }
+
+        impl Generator for WorldGenerator {
+            fn gen(&mut self) -> WorldType {
+                let mut map: Vec<Vec<Tile>> = (0..self.size)
+                    .map(|_| {
+                        (0..self.size)
+                            .map(|_| Tile {
+                                tile_type: self.tile_type,
+                                content: Content::None,
+                                elevation: 0,
+                            })
+                            .collect()
+                    })
+                    .collect();
+                // two trees next to where the robot stops
+                map[1][2] = Tile {
+                    tile_type: self.tile_type,
+                    content: Content::Tree(3),
+                    elevation: 0,
+                };
+                map[2][1] = Tile {
+                    tile_type: self.tile_type,
+                    content: Content::Tree(2),
+                    elevation: 0,
+                };
+
+                let environmental_conditions =
+                    EnvironmentalConditions::new(&vec![Sunny], 15, 12).unwrap();
+                return (map, (0, 0), environmental_conditions, 10.0, None);
+            }
+        }
+
+        let r = TestRobot(Robot::new());
+        let runner = Runner::new(
+            Box::new(r),
+            &mut WorldGenerator {
+                size: 5,
+                tile_type: TileType::Grass,
+            },
+        );
+        let _ = runner.unwrap().game_tick();
+    }
+
+    /// What the robot of `run_plan_executor` does besides calling the executor.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Disruption {
+        None,
+        // at the first tick, the robot collects the tile on its right and puts 1 unit of garbage back
+        // into the tile on its right once the executor moved
+        FillBin,
+        // at the second tick, the robot spends its energy right before the executor goes on
+        Drain,
+    }
+
+    /// Runs `executor` for up to `ticks` game ticks in a grass world of `size` tiles, with the robot
+    /// in the top-left corner. `tiles` are placed as `(row, column, tile_type, content)`; the robot
+    /// discovers the ones holding a content at the first tick. Undiscovered tiles cost as much as grass.
+    ///
+    /// Returns the executor and the result of every call to `step`, until it is done or fails.
+    fn run_plan_executor(
+        executor: PlanExecutor,
+        disruption: Disruption,
+        size: usize,
+        tiles: Vec<(usize, usize, TileType, Content)>,
+        ticks: usize,
+    ) -> (PlanExecutor, Vec<Result<ExecutionStatus, ToolError>>) {
+        struct TestRobot {
+            robot: Robot,
+            disruption: Disruption,
+            discover: Vec<(usize, usize)>,
+            executor: Arc<Mutex<PlanExecutor>>,
+            results: Arc<Mutex<Vec<Result<ExecutionStatus, ToolError>>>>,
+            index: ResourceIndex,
+            tick: usize,
+        }
+        impl Runnable for TestRobot {
+            fn process_tick(&mut self, world: &mut World) {
+                if matches!(
+                    self.results.lock().unwrap().last(),
+                    Some(Ok(ExecutionStatus::Done)) | Some(Err(_))
+                ) {
+                    return;
+                }
+                let tick = self.tick;
+                self.tick += 1;
+                match (self.disruption, tick) {
+                    (_, 0) => {
+                        let discover = self.discover.clone();
+                        discover_tiles(self, world, &discover).unwrap();
+                        if self.disruption == Disruption::FillBin {
+                            destroy(self, world, Direction::Right).unwrap();
+                        }
+                    }
+                    (Disruption::Drain, 1) => {
+                        // back and forth, ending on the same tile
+                        while self.get_energy().get_energy_level() >= 2 {
+                            go(self, world, Direction::Left).unwrap();
+                            go(self, world, Direction::Right).unwrap();
+                        }
+                    }
+                    _ => {}
+                }
+
+                let executor = Arc::clone(&self.executor);
+                let mut index = mem::replace(&mut self.index, ResourceIndex::new());
+                let result = executor.lock().unwrap().step(self, world, &mut index, tick);
+                self.index = index;
+                self.results.lock().unwrap().push(result);
+
+                if self.disruption == Disruption::FillBin && tick == 0 {
+                    put(self, world, Garbage(0), 1, Direction::Right).unwrap();
+                }
+            }
+            fn handle_event(&mut self, _: Event) {}
+            fn get_energy(&self) -> &Energy {
+                &self.robot.energy
+            }
+            fn get_energy_mut(&mut self) -> &mut Energy {
+                &mut self.robot.energy
+            }
+            fn get_coordinate(&self) -> &Coordinate {
+                &self.robot.coordinate
+            }
+            fn get_coordinate_mut(&mut self) -> &mut Coordinate {
+                &mut self.robot.coordinate
+            }
+            fn get_backpack(&self) -> &BackPack {
+                &self.robot.backpack
+            }
+            fn get_backpack_mut(&mut self) -> &mut BackPack {
+                &mut self.robot.backpack
+            }
+        }
+
+        struct WorldGenerator {
+            size: usize,
+            tiles: Vec<(usize, usize, TileType, Content)>,
+        }
+
+        impl Generator for WorldGenerator {
+            fn gen(&mut self) -> WorldType {
+                let mut map: Vec<Vec<Tile>> = vec![
+                    vec![
+                        Tile {
+                            tile_type: TileType::Grass,
+                            content: Content::None,
+                            elevation: 0,
+                        };
+                        self.size
+                    ];
+                    self.size
+                ];
+                for (row, column, tile_type, content) in self.tiles.iter() {
+                    map[*row][*column] = Tile {
+                        tile_type: *tile_type,
+                        content: content.clone(),
+                        elevation: 0,
+                    };
+                }
+                let environmental_conditions =
+                    EnvironmentalConditions::new(&vec![Sunny], 15, 12).unwrap();
+                (map, (0, 0), environmental_conditions, 10.0, None)
+            }
+        }
+
+        let executor = Arc::new(Mutex::new(executor.with_path_options(PathOptions {
+            unknown_tiles: UnknownTiles::Cost(TileType::Grass.properties().cost()),
+            ..PathOptions::default()
+        })));
+        let results = Arc::new(Mutex::new(vec![]));
+        let robot = TestRobot {
+            robot: Robot::new(),
+            disruption,
+            discover: tiles
+                .iter()
+                .filter(|(.., content)| *content != Content::None)
+                .map(|(row, column, ..)| (*row, *column))
+                .collect(),
+            executor: Arc::clone(&executor),
+            results: Arc::clone(&results),
+            index: ResourceIndex::new(),
+            tick: 0,
+        };
+        let mut runner = Runner::new(Box::new(robot), &mut WorldGenerator { size, tiles }).unwrap();
+        for _ in 0..ticks {
+            let _ = runner.game_tick();
+        }
+
+        let executor = mem::replace(&mut *executor.lock().unwrap(), PlanExecutor::new(vec![]));
+        let results = mem::take(&mut *results.lock().unwrap());
+        (executor, results)
+    }
+
+    #[test]
+    fn test_plan_executor_blocked_tile() {
+        // deep water, not discovered yet, on the straight way to the tree
+        let tiles = vec![
+            (0, 3, TileType::DeepWater, Content::None),
+            (0, 5, TileType::Grass, Tree(2)),
+        ];
+        let goals = vec![Goal::Gather {
+            content: Tree(0),
+            amount: 2,
+        }];
+
+        let (executor, results) = run_plan_executor(
+            PlanExecutor::new(goals.clone()),
+            Disruption::None,
+            6,
+            tiles.clone(),
+            20,
+        );
+        let statuses: Vec<ExecutionStatus> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ExecutionStatus::Running,
+                ExecutionStatus::Running,
+                ExecutionStatus::Replanning,
+                ExecutionStatus::Running,
+                ExecutionStatus::Running,
+                ExecutionStatus::Running,
+                ExecutionStatus::Running,
+                ExecutionStatus::Done,
+            ]
+        );
+        assert_eq!(executor.replans(), 1);
+        assert!(executor
+            .last_failure()
+            .is_some_and(|e| !matches!(e, LibError::NotEnoughEnergy)));
+        // the new plan goes around the tile where the robot failed to go
+        let water = MapCoordinate::new(3, 0);
+        assert_eq!(executor.blocked(), &HashSet::from([water]));
+        assert!(executor
+            .plan()
+            .unwrap()
+            .actions
+            .iter()
+            .all(|action| !matches!(action, Action::Move(path) if path.tiles.contains(&water))));
+
+        // the executor gives up once it replanned as many times as allowed
+        let (executor, results) = run_plan_executor(
+            PlanExecutor::new(goals).with_max_replans(0),
+            Disruption::None,
+            6,
+            tiles,
+            20,
+        );
+        assert!(matches!(
+            results[..],
+            [
+                Ok(ExecutionStatus::Running),
+                Ok(ExecutionStatus::Running),
+                Err(ToolError::Other(_))
+            ]
+        ));
+        assert_eq!(executor.replans(), 0);
+    }
+
+    #[test]
+    fn test_plan_executor_stale_plan() {
+        let tiles = vec![
+            (0, 1, TileType::Grass, Garbage(4)),
+            (0, 2, TileType::Grass, Bin(0..3)),
+            (3, 0, TileType::Grass, Bin(0..3)),
+        ];
+        let goals = vec![Goal::Deliver {
+            content: Garbage(0),
+            amount: 3,
+        }];
+
+        // the closest bin can only take 2 of the 3 units planned: the rest goes to the other bin
+        let (executor, results) =
+            run_plan_executor(PlanExecutor::new(goals), Disruption::FillBin, 5, tiles, 20);
+        let statuses: Vec<ExecutionStatus> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ExecutionStatus::Running,
+                ExecutionStatus::Replanning,
+                ExecutionStatus::Running,
+                ExecutionStatus::Running,
+                ExecutionStatus::Running,
+                ExecutionStatus::Done,
+            ]
+        );
+        assert_eq!(executor.replans(), 1);
+        // delivering less than planned is not a failure of robotics_lib
+        assert!(executor.last_failure().is_none());
+        assert_eq!(executor.delivered(&Garbage(0)), 3);
+        assert!(executor.remaining_goals().is_empty());
+    }
+
+    #[test]
+    fn test_plan_executor_waiting() {
+        // the copy of the maximum energy of robotics_lib is still up to date
+        assert_eq!(Robot::new().energy.get_energy_level(), MAX_ENERGY_LEVEL);
+
+        let tiles = vec![(0, 2, TileType::Grass, Tree(2))];
+        let goals = vec![Goal::Gather {
+            content: Tree(0),
+            amount: 2,
+        }];
+
+        // the robot runs out of energy next to the tree, and collects it once recharged
+        let (executor, results) =
+            run_plan_executor(PlanExecutor::new(goals), Disruption::Drain, 5, tiles, 100);
+        let statuses: Vec<ExecutionStatus> = results.into_iter().map(Result::unwrap).collect();
+        assert!(statuses.len() > 2);
+        assert_eq!(statuses[0], ExecutionStatus::Running);
+        assert!(statuses[1..statuses.len() - 1]
+            .iter()
+            .all(|status| *status == ExecutionStatus::Waiting));
+        assert_eq!(statuses.last(), Some(&ExecutionStatus::Done));
+        assert_eq!(executor.replans(), 0);
+        assert!(matches!(
+            executor.last_failure(),
+            Some(LibError::NotEnoughEnergy)
+        ));
+    }
+}
@@ -1,29 +1,331 @@
 pub mod tile_mapper {
+    use std::borrow::Cow;
     use std::collections::HashMap;
     use std::error::Error;
     use std::mem::{discriminant, Discriminant};
     use std::ops::Range;
 
-    use robotics_lib::interface::{robot_map, Tools};
+    use robotics_lib::interface::Tools;
     use robotics_lib::runner::Runnable;
     use robotics_lib::world::tile::{Content, Tile};
-    use robotics_lib::world::World;
 
-    use crate::coordinates::map_coordinate::MapCoordinate;
-    use crate::errors::tool_errors::ToolError::{ContentNotDiscovered, WorldNotDiscovered};
+    use crate::coordinates::map_coordinate::{Locatable, MapCoordinate};
+    use crate::errors::tool_errors::ToolError;
+    use crate::errors::tool_errors::ToolError::{ContentNotDiscovered, Other, WorldNotDiscovered};
+    use crate::source::map_source::MapSource;
 
     pub struct TileMapper {}
 
     impl Tools for TileMapper {}
 
-    pub(crate) type ContentQuantity = (Option<usize>, Option<Range<usize>>);
+    pub type ContentQuantity = (Option<usize>, Option<Range<usize>>);
+
+    /// Which property of a container (`Bin`, `Crate` or `Bank`) is maximized by `find_container`.
+    ///
+    /// ## Variants
+    ///
+    /// - `LargestCapacity`: the container able to hold the most units overall.
+    /// - `MostFreeCapacity`: the container able to accept the most units right now.
+    /// - `Fullest`: the container holding the most units.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ContainerQuery {
+        LargestCapacity,
+        MostFreeCapacity,
+        Fullest,
+    }
+
+    impl ContainerQuery {
+        /// Returns the value of a container's range according to the query.
+        pub fn value(&self, range: &Range<usize>) -> usize {
+            match self {
+                ContainerQuery::LargestCapacity => range.end,
+                ContainerQuery::MostFreeCapacity => range.end.saturating_sub(range.start),
+                ContainerQuery::Fullest => range.start,
+            }
+        }
+    }
+
+    /// The map returned by `collection`: every discovered tile grouped by the kind of its `Content`.
+    pub type ResourceMap = HashMap<Discriminant<Content>, Vec<(MapCoordinate, ContentQuantity)>>;
+
+    /// Returns one default instance of every `Content` variant, used to go back from a
+    /// `Discriminant<Content>` to a readable name.
+    pub(crate) fn all_contents() -> Vec<Content> {
+        vec![
+            Content::Rock(0),
+            Content::Tree(0),
+            Content::Garbage(0),
+            Content::Fire,
+            Content::Coin(0),
+            Content::Bin(0..0),
+            Content::Crate(0..0),
+            Content::Bank(0..0),
+            Content::Water(0),
+            Content::Market(0),
+            Content::Fish(0),
+            Content::Building,
+            Content::Bush(0),
+            Content::JollyBlock(0),
+            Content::Scarecrow,
+            Content::None,
+        ]
+    }
+
+    /// Returns the name of a `Content` variant, without its quantity.
+    pub fn content_name(content: &Content) -> &'static str {
+        match content {
+            Content::Rock(_) => "Rock",
+            Content::Tree(_) => "Tree",
+            Content::Garbage(_) => "Garbage",
+            Content::Fire => "Fire",
+            Content::Coin(_) => "Coin",
+            Content::Bin(_) => "Bin",
+            Content::Crate(_) => "Crate",
+            Content::Bank(_) => "Bank",
+            Content::Water(_) => "Water",
+            Content::Market(_) => "Market",
+            Content::Fish(_) => "Fish",
+            Content::Building => "Building",
+            Content::Bush(_) => "Bush",
+            Content::JollyBlock(_) => "JollyBlock",
+            Content::Scarecrow => "Scarecrow",
+            Content::None => "None",
+        }
+    }
+
+    /// Returns the name of the `Content` variant identified by `kind`.
+    pub fn kind_name(kind: &Discriminant<Content>) -> &'static str {
+        all_contents()
+            .iter()
+            .find(|content| discriminant(*content) == *kind)
+            .map(content_name)
+            .unwrap_or("Unknown")
+    }
+
+    /// Formats a `ContentQuantity` as `n` for amounts, `start..end` for ranges and `-` otherwise.
+    pub(crate) fn format_quantity(quantity: &ContentQuantity) -> String {
+        match quantity {
+            (Some(q), _) => q.to_string(),
+            (None, Some(range)) => format!("{}..{}", range.start, range.end),
+            (None, None) => "-".to_string(),
+        }
+    }
+
+    /// Returns the amount of content held by a tile: the quantity for countable contents and
+    /// the amount already stored for containers (`Bin`, `Crate`, `Bank`), whose range goes
+    /// from the stored amount to the maximum capacity.
+    pub(crate) fn stored_amount(quantity: &ContentQuantity) -> usize {
+        match quantity {
+            (Some(q), _) => *q,
+            (None, Some(range)) => range.start,
+            (None, None) => 0,
+        }
+    }
+
+    /// Parses a `ContentQuantity` written by `format_quantity`.
+    pub(crate) fn parse_quantity(text: &str) -> Option<ContentQuantity> {
+        if text == "-" {
+            return Some((None, None));
+        }
+        match text.split_once("..") {
+            Some((start, end)) => Some((None, Some(start.parse().ok()?..end.parse().ok()?))),
+            None => Some((Some(text.parse().ok()?), None)),
+        }
+    }
+
+    /// Groups the tiles of a grid such as the one returned by `robot_map` by the kind of their `Content`.
+    ///
+    /// With the `rayon` feature the rows are scanned in parallel; the result is the same either way.
+    pub(crate) fn collect_resources(robot_world: &[Vec<Option<Tile>>]) -> ResourceMap {
+        #[cfg(feature = "rayon")]
+        let resources = scan_parallel(robot_world);
+        #[cfg(not(feature = "rayon"))]
+        let resources = scan_sequential(robot_world);
+        resources
+    }
+
+    /// Scans the rows of `rows`, the first of which is row `first_row` of the map.
+    fn scan_rows(rows: &[Vec<Option<Tile>>], first_row: usize) -> ResourceMap {
+        // HashMap instantiation
+        let mut object_list: ResourceMap = HashMap::new();
+
+        // iterate through every tile in the world
+        for (row, row_vector) in rows.iter().enumerate() {
+            for (column, element) in row_vector.iter().enumerate() {
+                match element {
+                    None => {}
+                    Some(tile) => {
+                        // call the `insert_in_map` function defined below if content was found in the tile
+                        if tile.content != Content::None {
+                            TileMapper::insert_in_map(
+                                tile,
+                                &mut object_list,
+                                first_row + row,
+                                column,
+                            )
+                        }
+                    }
+                }
+            }
+        }
+        object_list
+    }
+
+    /// Groups the tiles of a grid by the kind of their `Content` on the current thread.
+    ///
+    /// The tiles of every kind are in reading order.
+    pub fn scan_sequential(robot_world: &[Vec<Option<Tile>>]) -> ResourceMap {
+        scan_rows(robot_world, 0)
+    }
+
+    /// Groups the tiles of a grid by the kind of their `Content`, scanning the rows in parallel.
+    ///
+    /// Every thread builds a partial map of consecutive rows, and the partial maps are merged
+    /// in row order, so the result is identical to `scan_sequential`.
+    #[cfg(feature = "rayon")]
+    pub fn scan_parallel(robot_world: &[Vec<Option<Tile>>]) -> ResourceMap {
+        use rayon::prelude::*;
+
+        // a few chunks per thread, so that threads finishing early can steal work
+        let chunk = (robot_world.len() / (rayon::current_num_threads() * 4)).max(1);
+        robot_world
+            .par_chunks(chunk)
+            .enumerate()
+            .map(|(index, rows)| scan_rows(rows, index * chunk))
+            // `reduce` only merges neighbouring chunks, left before right
+            .reduce(HashMap::new, |mut left, right| {
+                for (kind, tiles) in right {
+                    left.entry(kind).or_default().extend(tiles);
+                }
+                left
+            })
+    }
+
+    /// Where a `ResourceEntry` reads its quantity from.
+    #[derive(Debug, Clone, Copy)]
+    enum Stored<'a> {
+        Tile(&'a Content),
+        Map(&'a ContentQuantity),
+    }
+
+    /// A discovered tile holding some content, borrowed from the grid or the `ResourceMap`
+    /// it was found in, so that queries can walk the map without copying it.
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// use tile_resource_mapper_tool::tool::tile_mapper::grid_entries;
+    ///
+    /// let robot_world = robot_map(world).unwrap();
+    /// let coins: usize = grid_entries(&robot_world, &Content::Coin(0))
+    ///     .map(|entry| entry.amount())
+    ///     .sum();
+    /// ```
+    #[derive(Debug, Clone, Copy)]
+    pub struct ResourceEntry<'a> {
+        coordinate: MapCoordinate,
+        kind: Discriminant<Content>,
+        stored: Stored<'a>,
+    }
+
+    impl<'a> ResourceEntry<'a> {
+        /// Gets the coordinate of the tile.
+        pub fn coordinate(&self) -> MapCoordinate {
+            self.coordinate
+        }
+
+        /// Gets the kind of the content of the tile.
+        pub fn kind(&self) -> Discriminant<Content> {
+            self.kind
+        }
+
+        /// Gets the content of the tile, if the entry was read from a grid of tiles.
+        pub fn content(&self) -> Option<&'a Content> {
+            match self.stored {
+                Stored::Tile(content) => Some(content),
+                Stored::Map(_) => None,
+            }
+        }
+
+        /// Gets the quantity of the content, as stored by `collection`.
+        pub fn quantity(&self) -> ContentQuantity {
+            match self.stored {
+                Stored::Tile(content) => content.get_value(),
+                Stored::Map(quantity) => quantity.clone(),
+            }
+        }
+
+        /// Gets the amount of content held by the tile: the quantity for countable contents
+        /// and the amount already stored for containers.
+        pub fn amount(&self) -> usize {
+            stored_amount(&self.quantity())
+        }
+    }
+
+    /// Iterates over the tiles of a grid holding the same kind of `content`, in reading order,
+    /// without building a `ResourceMap`.
+    ///
+    /// As in `collection`, tiles without content are never returned.
+    pub fn grid_entries<'a>(
+        robot_world: &'a [Vec<Option<Tile>>],
+        content: &Content,
+    ) -> impl Iterator<Item = ResourceEntry<'a>> + 'a {
+        let kind = discriminant(content);
+        robot_world
+            .iter()
+            .enumerate()
+            .flat_map(move |(row, row_vector)| {
+                row_vector
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(column, element)| match element {
+                        Some(tile)
+                            if tile.content != Content::None
+                                && discriminant(&tile.content) == kind =>
+                        {
+                            Some(ResourceEntry {
+                                coordinate: MapCoordinate::new(column, row),
+                                kind,
+                                stored: Stored::Tile(&tile.content),
+                            })
+                        }
+                        _ => None,
+                    })
+            })
+    }
+
+    /// Iterates over the tiles of `resources` holding the same kind of `content`, in the order
+    /// they are stored.
+    pub fn map_entries<'a>(
+        resources: &'a ResourceMap,
+        content: &Content,
+    ) -> impl Iterator<Item = ResourceEntry<'a>> + 'a {
+        let kind = discriminant(content);
+        resources
+            .get(&kind)
+            .into_iter()
+            .flatten()
+            .map(move |(coordinate, quantity)| ResourceEntry {
+                coordinate: *coordinate,
+                kind,
+                stored: Stored::Map(quantity),
+            })
+    }
+
+    /// Returns the kind of the `Content` variant called `name`, if any.
+    pub fn kind_from_name(name: &str) -> Option<Discriminant<Content>> {
+        all_contents()
+            .iter()
+            .find(|content| content_name(content) == name)
+            .map(discriminant)
+    }
 
     impl TileMapper {
         /// The `collection` function stores the number of elements found in any tile discovered by the robot and returns them in a hashmap.
         ///
         /// # Arguments
         ///
-        /// * `world` - A reference to the `World`.
+        /// * `source` - Where the tiles are read from, e.g. a reference to the `World` or a grid returned by `robot_map`.
         ///
         /// # Returns
         ///
@@ -42,7 +344,7 @@ pub mod tile_mapper {
         ///
         /// let mapper = TileMapper{};
         ///
-        /// let result = mapper::collection(world);
+        /// let result = TileMapper::collection(world);
         ///
         /// match result {
         ///     Some(hashmap) => // handle hashmap,
@@ -50,53 +352,11 @@ pub mod tile_mapper {
         /// }
         /// ```
 
-        pub fn collection(
-            world: &World,
-        ) -> Option<HashMap<Discriminant<Content>, Vec<(MapCoordinate, ContentQuantity)>>> {
-            // HashMap instantiation
-            let mut object_list: HashMap<
-                Discriminant<Content>,
-                Vec<(MapCoordinate, ContentQuantity)>,
-            > = HashMap::new();
-
-            // check whether the world has been already discovered or not
-            match robot_map(&world) {
-                None => {
-                    return None;
-                }
-                Some(robot_world) => {
-                    // iterate through every tile in the world
-
-                    for (row, row_vector) in robot_world.iter().enumerate() {
-                        for (column, element) in row_vector.iter().enumerate() {
-                            match element {
-                                None => {}
-                                Some(tile) => {
-                                    // call the `insert_in_map` function defined below if content was found in the tile
-                                    if tile.content != Content::None {
-                                        TileMapper::insert_in_map(
-                                            tile,
-                                            &mut object_list,
-                                            row,
-                                            column,
-                                        )
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            // return the HashMap
-            Some(object_list)
+        pub fn collection<S: MapSource + ?Sized>(source: &S) -> Option<ResourceMap> {
+            source.resources().map(Cow::into_owned)
         }
         /// This function inserts the coordinates of a `tile` and the number of elements in that `tile`
-        fn insert_in_map(
-            tile: &Tile,
-            list: &mut HashMap<Discriminant<Content>, Vec<(MapCoordinate, ContentQuantity)>>,
-            row: usize,
-            col: usize,
-        ) {
+        fn insert_in_map(tile: &Tile, list: &mut ResourceMap, row: usize, col: usize) {
             let coord = (col, row);
             let value: ContentQuantity = tile.content.get_value();
             let content = discriminant(&tile.content);
@@ -108,49 +368,28 @@ pub mod tile_mapper {
                 .or_insert(vec![(coord.into(), value)]);
         }
 
-        fn collection2(
-            robot_world: Vec<Vec<Option<Tile>>>,
-        ) -> Option<HashMap<Discriminant<Content>, Vec<(MapCoordinate, ContentQuantity)>>> {
-            // HashMap instantiation
-            let mut object_list: HashMap<
-                Discriminant<Content>,
-                Vec<(MapCoordinate, ContentQuantity)>,
-            > = HashMap::new();
-
-            for (row, row_vector) in robot_world.iter().enumerate() {
-                for (column, element) in row_vector.iter().enumerate() {
-                    match element {
-                        None => {}
-                        Some(tile) => {
-                            // call the `insert_in_map` function defined below if content was found in the tile
-                            if tile.content != Content::None {
-                                TileMapper::insert_in_map(
-                                    tile,
-                                    &mut object_list,
-                                    row,
-                                    column,
-                                )
-                            }
-                        }
-                    }
-                }
-            }
-            // return the HashMap
-            Some(object_list)
-        }
-
         /// Finds the closest Tile to the Robot at that time, containing a certain Content.
         ///
         /// # Arguments
         ///
-        /// * `world` - A reference to the World in which the coordinates are computed.
-        /// * `robot` - A reference to an object implementing the "Runnable" trait.
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `origin` - The position distances are measured from, e.g. the robot or a `MapCoordinate`.
         /// * `content` - The Content we are looking for.
         ///
         /// # Returns
         ///
         /// Returns the Map coordinates of the closest Tile containing the requested Content.
-        /// Returns an error if it fails to find a tile.
+        /// Returns `WorldNotDiscovered` if the source has discovered nothing yet, e.g. an index
+        /// never updated, and `ContentNotDiscovered` if no discovered tile holds `content`.
+        ///
+        /// # Notes
+        ///
+        /// In case of two Tiles at the same distance, the first one in reading order is returned,
+        /// whether the source is searched through its spatial index or tile by tile.
+        ///
+        /// Distances are Euclidean, as `MapCoordinate::get_distance`: teleports, elevation and
+        /// impassable tiles are ignored. Use `find_closest_by_cost` to rank tiles by the energy
+        /// needed to reach them, teleport hops included.
         ///
         /// # Example
         /// ```ignore
@@ -166,92 +405,72 @@ pub mod tile_mapper {
         /// }
         /// ```
 
-        pub fn find_closest(
+        pub fn find_closest<S: MapSource + ?Sized, K>(
             &self,
-            world: &World,
-            robot: &impl Runnable,
+            source: &S,
+            origin: &impl Locatable<K>,
             content: Content,
-        ) -> Result<MapCoordinate, Box<dyn Error>> {
-            let hashmap = TileMapper::collection(world);
-            match hashmap {
-                Some(map) => {
-                    // check if the hashmap contains the searched content
-                    let cont = discriminant(&content);
-                    return if map.contains_key(&cont) {
-                        let vec = map.get(&cont);
-                        let robot_coordinates = MapCoordinate::new(
-                            robot.get_coordinate().get_col(),
-                            robot.get_coordinate().get_row(),
-                        );
-
-                        let mut closest_coordinates = MapCoordinate::new(100000, 100000);
-                        if let Some(v) = vec {
-                            // iterate through the vector and search for the closest tile
-                            for element in v.iter() {
-                                // search for the smallest distance between the tiles and the robot
-                                let old_distance =
-                                    closest_coordinates.get_distance(&robot_coordinates);
-                                let new_distance = element.0.get_distance(&robot_coordinates);
-                                if new_distance < old_distance {
-                                    closest_coordinates = element.0;
-                                }
-                            }
-                        }
-                        Ok(closest_coordinates)
-                    } else {
-                        Err(Box::new(ContentNotDiscovered))
-                    };
+        ) -> Result<MapCoordinate, ToolError> {
+            let cont = discriminant(&content);
+            let origin_coordinates = origin.location();
+            // sources keeping a spatial index are searched without scanning every tile
+            if let Some(spatial) = source.spatial() {
+                // an index built from a resource map has no discovered tiles, but is not empty
+                let nothing_discovered = spatial.is_empty()
+                    && source
+                        .discovered()
+                        .is_none_or(|mask| mask.discovered_count() == 0);
+                if nothing_discovered {
+                    return Err(WorldNotDiscovered);
                 }
-                None => Err(Box::new(WorldNotDiscovered)),
+                return spatial
+                    .nearest(&content, &origin_coordinates)
+                    .map(|(coordinates, _)| *coordinates)
+                    .ok_or(ContentNotDiscovered(cont));
+            }
+            // the closest tile found so far, with its distance
+            let mut closest: Option<(MapCoordinate, f64)> = None;
+            let discovered = source.visit(&content, &mut |entry| {
+                let coordinates = entry.coordinate();
+                let distance = coordinates.get_distance(&origin_coordinates);
+                closest = match closest {
+                    Some((old, old_distance))
+                        if old_distance < distance
+                            || (old_distance == distance && old < coordinates) =>
+                    {
+                        Some((old, old_distance))
+                    }
+                    _ => Some((coordinates, distance)),
+                };
+            });
+            // check if the world has already been discovered
+            if !discovered {
+                return Err(WorldNotDiscovered);
             }
+            closest
+                .map(|(coordinates, _)| coordinates)
+                .ok_or(ContentNotDiscovered(cont))
         }
 
-
+        /// Same as `find_closest`, for a grid returned by `robot_map`.
+        #[deprecated(
+            note = "pass the grid to `find_closest` instead: `find_closest(&map, robot, content)`"
+        )]
         pub fn find_closest2(
             &self,
             world: Vec<Vec<Option<Tile>>>,
             robot: &impl Runnable,
             content: Content,
         ) -> Result<MapCoordinate, Box<dyn Error>> {
-            let hashmap = TileMapper::collection2(world);
-            match hashmap {
-                Some(map) => {
-                    // check if the hashmap contains the searched content
-                    let cont = discriminant(&content);
-                    return if map.contains_key(&cont) {
-                        let vec = map.get(&cont);
-                        let robot_coordinates = MapCoordinate::new(
-                            robot.get_coordinate().get_col(),
-                            robot.get_coordinate().get_row(),
-                        );
-
-                        let mut closest_coordinates = MapCoordinate::new(100000, 100000);
-                        if let Some(v) = vec {
-                            // iterate through the vector and search for the closest tile
-                            for element in v.iter() {
-                                // search for the smallest distance between the tiles and the robot
-                                let old_distance =
-                                    closest_coordinates.get_distance(&robot_coordinates);
-                                let new_distance = element.0.get_distance(&robot_coordinates);
-                                if new_distance < old_distance {
-                                    closest_coordinates = element.0;
-                                }
-                            }
-                        }
-                        Ok(closest_coordinates)
-                    } else {
-                        Err(Box::new(ContentNotDiscovered))
-                    };
-                }
-                None => Err(Box::new(WorldNotDiscovered)),
-            }
+            Ok(self.find_closest(&world, robot, content)?)
         }
+
         /// Find the most loaded Tile, given a type of Content.
         ///
         /// # Arguments
         ///
-        /// * `world` - A reference to the World in which the coordinates are computed.
-        /// * `robot` - A reference to an object implementing the "Runnable" trait.
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `origin` - The position distances are measured from, e.g. the robot or a `MapCoordinate`.
         /// * `content` - The Content we're searching for.
         ///
         /// # Returns
@@ -262,6 +481,10 @@ pub mod tile_mapper {
         ///    
         /// In case of two Tiles with the same amount of Content, the closest one to the robot is returned.
         ///
+        /// Containers (`Bin`, `Crate`, `Bank`) are compared by the units they hold, the start of their
+        /// range, as `find_container` with `ContainerQuery::Fullest`: use `find_container` to choose
+        /// by capacity or free capacity instead.
+        ///
         /// # Example
         /// ```ignore
         /// use tile_resource_mapper_tool::tool::tile_mapper::TileMapper;
@@ -276,81 +499,110 @@ pub mod tile_mapper {
         /// }
         /// ```
         ///
-        pub fn find_most_loaded(
+        pub fn find_most_loaded<S: MapSource + ?Sized, K>(
             &self,
-            world: &World,
-            robot: &impl Runnable,
+            source: &S,
+            origin: &impl Locatable<K>,
             content: Content,
-        ) -> Result<MapCoordinate, Box<dyn Error>> {
-            let hashmap = TileMapper::collection(world);
+        ) -> Result<MapCoordinate, ToolError> {
+            // amounts are compared by quantity, containers by what they hold
+            self.find_best_tile(source, origin, &content, |quantity| match quantity {
+                (Some(q), None) => *q,
+                (None, Some(span)) => ContainerQuery::Fullest.value(span),
+                (_, _) => 0,
+            })
+        }
+
+        /// Finds a container (`Bin`, `Crate` or `Bank`) according to what is in it.
+        ///
+        /// # Arguments
+        ///
+        /// * `source` - Where the tiles are read from, e.g. a reference to the World.
+        /// * `origin` - The position distances are measured from, e.g. the robot or a `MapCoordinate`.
+        /// * `content` - The kind of container we're searching for.
+        /// * `query` - Which property of the container is maximized.
+        ///
+        /// # Returns
+        ///
+        /// Returns the Map coordinates of the container maximizing `query`.
+        /// Returns an error if `content` is not a container or no tile is found.
+        ///
+        /// # Notes
+        ///
+        /// A container `Bin(start..end)` holds `start` units and can hold at most `end` units,
+        /// as `put` increases the start of the range.
+        /// In case of two Tiles with the same value, the closest one to the robot is returned.
+        ///
+        /// # Example
+        /// ```ignore
+        /// use tile_resource_mapper_tool::tool::tile_mapper::{ContainerQuery, TileMapper};
+        ///
+        /// let mapper = TileMapper{};
+        ///
+        /// let result = mapper.find_container(world, robot, Content::Bin(0..0), ContainerQuery::MostFreeCapacity);
+        ///
+        /// match result {
+        ///     Ok(coord) => println!("x:{} y:{}",coord.get_width(),coord.get_height()),
+        ///     Err(e) => println!("{}",e)
+        /// }
+        /// ```
+        pub fn find_container<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            content: Content,
+            query: ContainerQuery,
+        ) -> Result<MapCoordinate, ToolError> {
+            if !matches!(
+                content,
+                Content::Bin(_) | Content::Crate(_) | Content::Bank(_)
+            ) {
+                return Err(Other(format!(
+                    "{} is not a container",
+                    content_name(&content)
+                )));
+            }
+            self.find_best_tile(source, origin, &content, |quantity| match quantity {
+                (None, Some(span)) => query.value(span),
+                (_, _) => 0,
+            })
+        }
+
+        /// Finds the tile with the same kind of `content` maximizing `key`.
+        ///
+        /// In case of two Tiles with the same value, the closest one to `origin` is returned.
+        fn find_best_tile<S: MapSource + ?Sized, K>(
+            &self,
+            source: &S,
+            origin: &impl Locatable<K>,
+            content: &Content,
+            key: impl Fn(&ContentQuantity) -> usize,
+        ) -> Result<MapCoordinate, ToolError> {
+            let origin_coordinates = origin.location();
+            // the best tile found so far, with its value; `None` until a tile is visited,
+            // so that tiles with an empty range or no content can be chosen too
+            let mut best: Option<(MapCoordinate, usize)> = None;
+            let discovered = source.visit(content, &mut |entry| {
+                let coordinates = entry.coordinate();
+                let value = key(&entry.quantity());
+                best = match best {
+                    Some((old, old_value))
+                        if old_value > value
+                            || (old_value == value
+                                && old.get_distance(&origin_coordinates)
+                                    <= coordinates.get_distance(&origin_coordinates)) =>
+                    {
+                        Some((old, old_value))
+                    }
+                    _ => Some((coordinates, value)),
+                };
+            });
             // check if the world has already been discovered
-            match hashmap {
-                Some(map) => {
-                    // check if the hashmap contains the searched content
-                    let item = discriminant(&content);
-                    return if map.contains_key(&item) {
-                        let vec = map.get(&item);
-                        let mut target_tile_coordinates = MapCoordinate::new(0, 0);
-                        if let Some(v) = vec {
-                            // instantiate quantity and range
-                            let mut quantity: usize = 0;
-                            let mut range: Range<usize> = 0..0;
-                            // iterate through the vector and search for the most loaded tile
-                            for element in v.iter() {
-                                // get and set coordinates of the last discovered tile with the higher amount of content
-                                // and if two tiles have the same quantity, set the closest tile
-                                match &element.1 {
-                                    (Some(q), None) => {
-                                        if q > &quantity {
-                                            quantity = *q;
-                                            target_tile_coordinates = element.0;
-                                        } else if q == &quantity {
-                                            // If two tiles have the same quantity, set the closest tile
-                                            let robot_coordinates = MapCoordinate::new(
-                                                robot.get_coordinate().get_col(),
-                                                robot.get_coordinate().get_row(),
-                                            );
-                                            let old_distance = target_tile_coordinates
-                                                .get_distance(&robot_coordinates);
-                                            let new_distance =
-                                                element.0.get_distance(&robot_coordinates);
-                                            if new_distance < old_distance {
-                                                quantity = *q;
-                                                target_tile_coordinates = element.0;
-                                            }
-                                        }
-                                    }
-                                    (None, Some(span)) => {
-                                        if span.len() > range.len() {
-                                            range = span.clone();
-                                            target_tile_coordinates = element.0;
-                                        } else if span.len() == range.len() {
-                                            // If two tiles have the same range, set the closest tile
-                                            let robot_coordinates = MapCoordinate::new(
-                                                robot.get_coordinate().get_col(),
-                                                robot.get_coordinate().get_row(),
-                                            );
-                                            let old_distance = target_tile_coordinates
-                                                .get_distance(&robot_coordinates);
-                                            let new_distance =
-                                                element.0.get_distance(&robot_coordinates);
-                                            if new_distance < old_distance {
-                                                range = span.clone();
-                                                target_tile_coordinates = element.0;
-                                            }
-                                        }
-                                    }
-                                    (_, _) => {}
-                                }
-                            }
-                        }
-                        Ok(target_tile_coordinates)
-                    } else {
-                        Err(Box::new(ContentNotDiscovered))
-                    };
-                }
-                None => Err(Box::new(WorldNotDiscovered)),
+            if !discovered {
+                return Err(WorldNotDiscovered);
             }
+            best.map(|(coordinates, _)| coordinates)
+                .ok_or(ContentNotDiscovered(discriminant(content)))
         }
     }
 }
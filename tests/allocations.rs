@@ -0,0 +1,176 @@
+//! Checks that the borrowing queries do not allocate.
+//!
+//! The counting allocator replaces the global allocator of this test binary only.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use robotics_lib::energy::Energy;
+use robotics_lib::event::events::Event;
+use robotics_lib::interface::{go, robot_map, Direction};
+use robotics_lib::runner::backpack::BackPack;
+use robotics_lib::runner::{Robot, Runnable, Runner};
+use robotics_lib::world::coordinates::Coordinate;
+use robotics_lib::world::environmental_conditions::EnvironmentalConditions;
+use robotics_lib::world::environmental_conditions::WeatherType::Sunny;
+use robotics_lib::world::tile::Content::{Bin, Coin, Rock};
+use robotics_lib::world::tile::{Content, Tile, TileType};
+use robotics_lib::world::world_generator::Generator;
+use robotics_lib::world::world_generator::World as WorldType;
+use robotics_lib::world::World;
+
+use another_one_bytes_the_dust_tile_resource_mapper_tool::coordinates::map_coordinate::MapCoordinate;
+use another_one_bytes_the_dust_tile_resource_mapper_tool::fixtures::test_maps::test_map;
+use another_one_bytes_the_dust_tile_resource_mapper_tool::region::map_region::Rect;
+use another_one_bytes_the_dust_tile_resource_mapper_tool::source::map_source::MapSource;
+use another_one_bytes_the_dust_tile_resource_mapper_tool::tool::tile_mapper::{
+    grid_entries, ContainerQuery, TileMapper,
+};
+
+/// Counts the allocations of every thread separately, so that tests running in parallel
+/// do not disturb each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // the counter may be gone while the thread is shutting down
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs `f`, returning its result and the number of allocations it made on this thread.
+fn allocations_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_borrowing_queries_do_not_allocate() {
+    let map = test_map(
+        20,
+        &[
+            (3, 4, Rock(2)),
+            (15, 2, Rock(5)),
+            (10, 10, Coin(3)),
+            (1, 1, Bin(2..10)),
+        ],
+    );
+    let resources = TileMapper::collection(&map).unwrap();
+    let mapper = TileMapper {};
+    let origin = MapCoordinate::new(0, 0);
+
+    let (closest, allocations) = allocations_during(|| mapper.find_closest(&map, &origin, Rock(0)));
+    assert_eq!(closest.unwrap(), MapCoordinate::new(3, 4));
+    assert_eq!(allocations, 0);
+
+    let (closest, allocations) =
+        allocations_during(|| mapper.find_closest(&resources, &origin, Rock(0)));
+    assert_eq!(closest.unwrap(), MapCoordinate::new(3, 4));
+    assert_eq!(allocations, 0);
+
+    let (most_loaded, allocations) =
+        allocations_during(|| mapper.find_most_loaded(&map, &origin, Rock(0)));
+    assert_eq!(most_loaded.unwrap(), MapCoordinate::new(15, 2));
+    assert_eq!(allocations, 0);
+
+    let (container, allocations) = allocations_during(|| {
+        mapper.find_container(&resources, &origin, Bin(0..0), ContainerQuery::Fullest)
+    });
+    assert_eq!(container.unwrap(), MapCoordinate::new(1, 1));
+    assert_eq!(allocations, 0);
+
+    let (rocks, allocations) = allocations_during(|| {
+        grid_entries(&map, &Rock(0))
+            .map(|entry| entry.amount())
+            .sum::<usize>()
+    });
+    assert_eq!(rocks, 7);
+    assert_eq!(allocations, 0);
+
+    // restricting the source filters the tiles while they are visited
+    let region = Rect::new(MapCoordinate::new(10, 0), MapCoordinate::new(19, 19));
+    let (closest, allocations) =
+        allocations_during(|| mapper.find_closest(&map.within(region), &origin, Rock(0)));
+    assert_eq!(closest.unwrap(), MapCoordinate::new(15, 2));
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_world_source_copies_the_grid() {
+    struct TestRobot(Robot);
+    impl Runnable for TestRobot {
+        fn process_tick(&mut self, world: &mut World) {
+            // discover the rock below the robot
+            go(self, world, Direction::Right).unwrap();
+            let mapper = TileMapper {};
+            let origin = MapCoordinate::new(0, 0);
+
+            let (from_world, world_allocations) =
+                allocations_during(|| mapper.find_closest(&*world, &origin, Rock(0)));
+            let grid = robot_map(world).unwrap();
+            let (from_grid, grid_allocations) =
+                allocations_during(|| mapper.find_closest(&grid, &origin, Rock(0)));
+            assert_eq!(from_world.unwrap(), MapCoordinate::new(1, 1));
+            assert_eq!(from_grid.unwrap(), MapCoordinate::new(1, 1));
+            // `robot_map` copies every row of the World at every query, the grid is only borrowed
+            assert!(world_allocations > grid.len());
+            assert_eq!(grid_allocations, 0);
+        }
+        fn handle_event(&mut self, _: Event) {}
+        fn get_energy(&self) -> &Energy {
+            &self.0.energy
+        }
+        fn get_energy_mut(&mut self) -> &mut Energy {
+            &mut self.0.energy
+        }
+        fn get_coordinate(&self) -> &Coordinate {
+            &self.0.coordinate
+        }
+        fn get_coordinate_mut(&mut self) -> &mut Coordinate {
+            &mut self.0.coordinate
+        }
+        fn get_backpack(&self) -> &BackPack {
+            &self.0.backpack
+        }
+        fn get_backpack_mut(&mut self) -> &mut BackPack {
+            &mut self.0.backpack
+        }
+    }
+
+    struct WorldGenerator;
+    impl Generator for WorldGenerator {
+        fn gen(&mut self) -> WorldType {
+            let mut map = vec![
+                vec![
+                    Tile {
+                        tile_type: TileType::Grass,
+                        content: Content::None,
+                        elevation: 0,
+                    };
+                    5
+                ];
+                5
+            ];
+            map[1][1].content = Rock(2);
+            let environmental_conditions =
+                EnvironmentalConditions::new(&vec![Sunny], 15, 12).unwrap();
+            (map, (0, 0), environmental_conditions, 10.0, None)
+        }
+    }
+
+    let runner = Runner::new(Box::new(TestRobot(Robot::new())), &mut WorldGenerator);
+    runner.unwrap().game_tick().unwrap();
+}